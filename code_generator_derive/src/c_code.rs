@@ -0,0 +1,651 @@
+use proc_macro2::{Delimiter, Group, Ident, Literal, Span, TokenStream, TokenTree};
+use quote::quote;
+
+/// The C operators made of more than one character
+const OPERATORS: [&str; 22] = [
+    "<<=", ">>=", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||",
+    "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "::",
+];
+
+/// The operators which are unary when they do not follow an operand
+const UNARY_OPERATORS: [&str; 6] = ["-", "+", "*", "&", "!", "~"];
+
+/// The keywords which are followed by a space, even before a parenthesis
+const SPACED_KEYWORDS: [&str; 3] = ["return", "case", "else"];
+
+/// The preprocessor directives, which are not interpolations after a `#`
+const DIRECTIVES: [&str; 10] = ["include", "define", "undef", "if", "ifdef", "ifndef", "elif", "else", "endif", "pragma"];
+
+/// The statements which have no generator
+const UNSUPPORTED_STATEMENTS: [&str; 3] = ["switch", "do", "else"];
+
+/// A token of a template, with the interpolations resolved
+#[derive(Clone)]
+enum Token {
+    Ident(Ident),
+    Literal(Literal),
+    Punct(char, bool, Span),
+    Group(Delimiter, Vec<Token>, Span),
+    Interpolation(Ident),
+    Directive(Ident),
+    /// A doc comment, with the text written for it
+    Comment(String, Span),
+}
+
+impl Token {
+    fn span(&self) -> Span {
+        match self {
+            Token::Ident(ident) | Token::Interpolation(ident) | Token::Directive(ident) => ident.span(),
+            Token::Literal(literal) => literal.span(),
+            Token::Punct(_, _, span) | Token::Group(_, _, span) | Token::Comment(_, span) => *span,
+        }
+    }
+
+    fn is_ident(&self, name: &str) -> bool {
+        matches!(self, Token::Ident(ident) if ident == name)
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        matches!(self, Token::Punct(punct, _, _) if *punct == c)
+    }
+
+    fn group(&self, delimiter: Delimiter) -> Option<&[Token]> {
+        match self {
+            Token::Group(group_delimiter, tokens, _) if *group_delimiter == delimiter => Some(tokens),
+            _ => None,
+        }
+    }
+}
+
+/// Turns a token stream into tokens, joining `#` and an identifier into an
+/// interpolation or a directive, and doc attributes into comments
+fn tokenize(stream: TokenStream) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut iter = stream.into_iter().peekable();
+    while let Some(tree) = iter.next() {
+        match tree {
+            TokenTree::Ident(ident) => tokens.push(Token::Ident(ident)),
+            TokenTree::Literal(literal) => tokens.push(Token::Literal(literal)),
+            TokenTree::Punct(punct) if punct.as_char() == '#' => match iter.peek() {
+                Some(TokenTree::Ident(ident)) => {
+                    let is_directive = DIRECTIVES.iter().any(|directive| ident == directive);
+                    tokens.push(if is_directive { Token::Directive(ident.clone()) } else { Token::Interpolation(ident.clone()) });
+                    iter.next();
+                }
+                Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+                    match doc_comment(group, "///", "/**") {
+                        Some(comment) => {
+                            tokens.push(Token::Comment(comment, punct.span()));
+                            iter.next();
+                        }
+                        None => tokens.push(Token::Punct('#', false, punct.span())),
+                    }
+                }
+                Some(TokenTree::Punct(bang)) if bang.as_char() == '!' => {
+                    let mut lookahead = iter.clone();
+                    lookahead.next();
+                    let comment = match lookahead.next() {
+                        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => doc_comment(&group, "//!", "/*!"),
+                        _ => None,
+                    };
+                    match comment {
+                        Some(comment) => {
+                            tokens.push(Token::Comment(comment, punct.span()));
+                            iter.next();
+                            iter.next();
+                        }
+                        None => tokens.push(Token::Punct('#', false, punct.span())),
+                    }
+                }
+                _ => tokens.push(Token::Punct('#', false, punct.span())),
+            },
+            TokenTree::Punct(punct) => tokens.push(Token::Punct(
+                punct.as_char(),
+                punct.spacing() == proc_macro2::Spacing::Joint,
+                punct.span()
+            )),
+            TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+                tokens.extend(tokenize(group.stream()));
+            }
+            TokenTree::Group(group) => tokens.push(group_token(&group)),
+        }
+    }
+    tokens
+}
+
+fn group_token(group: &Group) -> Token {
+    Token::Group(group.delimiter(), tokenize(group.stream()), group.span())
+}
+
+/// Writes the text of a `[doc = "..."]` attribute back as the comment it was
+/// written as, a line comment unless the text has several lines
+fn doc_comment(group: &Group, line_start: &str, block_start: &str) -> Option<String> {
+    let mut trees = group.stream().into_iter();
+    let is_doc = matches!(trees.next(), Some(TokenTree::Ident(ident)) if ident == "doc")
+        && matches!(trees.next(), Some(TokenTree::Punct(punct)) if punct.as_char() == '=');
+    let text = match (is_doc, trees.next(), trees.next()) {
+        (true, Some(TokenTree::Literal(literal)), None) => syn::parse2::<syn::LitStr>(TokenTree::Literal(literal).into()).ok()?.value(),
+        _ => return None,
+    };
+    Some(if text.contains('\n') {
+        format!("{}{}*/", block_start, text)
+    } else {
+        format!("{}{}", line_start, text)
+    })
+}
+
+/// A part of a generated piece of code
+enum Piece {
+    Text(String),
+    Interpolation(Ident),
+}
+
+/// What the last written token was, which decides the spacing of the next
+#[derive(Clone, Copy, PartialEq)]
+enum Previous {
+    Start,
+    Operand,
+    Keyword,
+    Comma,
+    Operator,
+    Unary,
+    Tight,
+}
+
+/// Writes tokens as C code, spacing them like hand written code
+struct Printer {
+    pieces: Vec<Piece>,
+    previous: Previous,
+    /// The first token which cannot be written as C code
+    error: Option<syn::Error>,
+}
+
+impl Printer {
+    fn new() -> Printer {
+        Printer { pieces: Vec::new(), previous: Previous::Start, error: None }
+    }
+
+    /// Prints the tokens, failing on tokens which cannot be written as C code
+    fn print_all(tokens: &[Token]) -> syn::Result<Vec<Piece>> {
+        let mut printer = Printer::new();
+        printer.print(tokens);
+        match printer.error {
+            Some(error) => Err(error),
+            None => Ok(printer.pieces),
+        }
+    }
+
+    fn fail(&mut self, span: Span, message: &str) {
+        if self.error.is_none() {
+            self.error = Some(syn::Error::new(span, message));
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(Piece::Text(last)) = self.pieces.last_mut() {
+            last.push_str(text);
+        } else {
+            self.pieces.push(Piece::Text(text.to_string()));
+        }
+    }
+
+    fn space_if(&mut self, spaced: &[Previous]) {
+        if spaced.contains(&self.previous) {
+            self.text(" ");
+        }
+    }
+
+    fn print(&mut self, tokens: &[Token]) {
+        let mut index = 0;
+        while index < tokens.len() {
+            match &tokens[index] {
+                Token::Ident(ident) => {
+                    self.space_if(&[Previous::Operand, Previous::Keyword, Previous::Comma, Previous::Operator]);
+                    let name = ident.to_string();
+                    self.text(&name);
+                    self.previous = if SPACED_KEYWORDS.contains(&name.as_str()) {
+                        Previous::Keyword
+                    } else {
+                        Previous::Operand
+                    };
+                }
+                Token::Literal(literal) => {
+                    self.space_if(&[Previous::Operand, Previous::Keyword, Previous::Comma, Previous::Operator]);
+                    self.text(&literal.to_string());
+                    self.previous = Previous::Operand;
+                }
+                Token::Interpolation(ident) => {
+                    self.space_if(&[Previous::Operand, Previous::Keyword, Previous::Comma, Previous::Operator]);
+                    self.pieces.push(Piece::Interpolation(ident.clone()));
+                    self.previous = Previous::Operand;
+                }
+                Token::Directive(ident) => self.fail(ident.span(), "a preprocessor directive must start a statement"),
+                Token::Comment(_, span) => self.fail(*span, "a comment must be placed before a statement"),
+                Token::Punct('#', _, span) => {
+                    self.fail(*span, "`#` must be followed by the name of a variable or a preprocessor directive");
+                }
+                Token::Group(delimiter, inner, _) => {
+                    let (open, close) = match delimiter {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::None => ("", ""),
+                    };
+                    if *delimiter == Delimiter::Brace {
+                        self.space_if(&[Previous::Operand, Previous::Keyword, Previous::Comma, Previous::Operator]);
+                    } else {
+                        self.space_if(&[Previous::Keyword, Previous::Comma, Previous::Operator]);
+                    }
+                    self.text(open);
+                    self.previous = Previous::Start;
+                    self.print(inner);
+                    self.text(close);
+                    self.previous = Previous::Operand;
+                }
+                Token::Punct(..) => {
+                    let (operator, length) = operator_at(&tokens[index..]);
+                    self.operator(&operator);
+                    index += length;
+                    continue;
+                }
+            }
+            index += 1;
+        }
+    }
+
+    fn operator(&mut self, operator: &str) {
+        match operator {
+            "," | ";" => {
+                self.text(operator);
+                self.previous = Previous::Comma;
+            }
+            "." | "->" | "::" => {
+                self.text(operator);
+                self.previous = Previous::Tight;
+            }
+            "++" | "--" if self.previous == Previous::Operand => self.text(operator),
+            "++" | "--" => {
+                self.space_if(&[Previous::Keyword, Previous::Comma, Previous::Operator]);
+                self.text(operator);
+                self.previous = Previous::Unary;
+            }
+            _ if UNARY_OPERATORS.contains(&operator) && self.previous != Previous::Operand => {
+                self.space_if(&[Previous::Keyword, Previous::Comma, Previous::Operator]);
+                self.text(operator);
+                self.previous = Previous::Unary;
+            }
+            _ => {
+                self.space_if(&[Previous::Operand, Previous::Keyword, Previous::Comma, Previous::Operator]);
+                self.text(operator);
+                self.previous = Previous::Operator;
+            }
+        }
+    }
+}
+
+/// Finds the longest C operator at the start of the tokens, returning it and
+/// the number of tokens it is made of
+fn operator_at(tokens: &[Token]) -> (String, usize) {
+    let mut text = String::new();
+    for token in tokens {
+        match token {
+            Token::Punct(c, _, _) => text.push(*c),
+            _ => break,
+        }
+        if let Token::Punct(_, false, _) = token {
+            break;
+        }
+    }
+    for operator in OPERATORS {
+        if text.starts_with(operator) {
+            return (operator.to_string(), operator.len());
+        }
+    }
+    (text[..1].to_string(), 1)
+}
+
+/// Generates an expression of a generator for a piece of code
+fn code_expr(tokens: &[Token]) -> syn::Result<TokenStream> {
+    let mut pieces = Printer::print_all(tokens)?;
+    if pieces.len() == 1 {
+        return Ok(match pieces.remove(0) {
+            Piece::Text(text) => quote!(::std::string::String::from(#text)),
+            Piece::Interpolation(ident) => quote!(::std::clone::Clone::clone(&#ident)),
+        });
+    }
+
+    let pieces = pieces.into_iter().map(|piece| match piece {
+        Piece::Text(text) => quote!(::std::boxed::Box::new(::std::string::String::from(#text))),
+        Piece::Interpolation(ident) => quote!(::std::boxed::Box::new(::std::clone::Clone::clone(&#ident))),
+    });
+    Ok(quote!(::code_generator::JoinedCode::new(::std::vec![#(#pieces),*])))
+}
+
+/// Generates a Name for a single identifier, or an interpolated Name
+fn name_expr(tokens: &[Token], span: Span, what: &str) -> syn::Result<TokenStream> {
+    match tokens {
+        [Token::Interpolation(ident)] => Ok(quote!(::std::clone::Clone::clone(&#ident))),
+        [] => Err(syn::Error::new(span, format!("expected {}", what))),
+        _ if tokens.iter().any(|token| matches!(token, Token::Interpolation(_))) => Err(syn::Error::new(
+            tokens[0].span(),
+            format!("an interpolated {} must be a single #name", what)
+        )),
+        _ => {
+            let pieces = Printer::print_all(tokens)?;
+            let text = match pieces.first() {
+                Some(Piece::Text(text)) => text.clone(),
+                _ => String::new(),
+            };
+            Ok(quote!(::code_generator::Name::new_with_type(#text, ::code_generator::NameType::Bypass)))
+        }
+    }
+}
+
+/// Generates the type and the name of a declaration like "uint8_t *data"
+fn declaration_expr(tokens: &[Token], span: Span) -> syn::Result<TokenStream> {
+    let (name, type_name) = match tokens.split_last() {
+        Some((name, type_name)) if !type_name.is_empty() => (name, type_name),
+        _ => return Err(syn::Error::new(span, "expected a type followed by a name")),
+    };
+    let type_name = type_expr(type_name, span)?;
+    let name = name_expr(std::slice::from_ref(name), span, "name")?;
+    Ok(quote!((#type_name, #name)))
+}
+
+/// Generates the Name of a type, with its pointer stars attached to it, so
+/// that the pointer alignment of the style is applied to declarations
+fn type_expr(tokens: &[Token], span: Span) -> syn::Result<TokenStream> {
+    let base_length = tokens.iter().rposition(|token| !token.is_punct('*')).map_or(0, |index| index + 1);
+    let stars = tokens.len() - base_length;
+    if stars == 0 {
+        return name_expr(tokens, span, "type");
+    }
+    if tokens[..base_length].iter().any(|token| matches!(token, Token::Interpolation(_))) {
+        return Err(syn::Error::new(tokens[0].span(), "an interpolated type cannot be a pointer, interpolate the whole type"));
+    }
+    let pieces = Printer::print_all(&tokens[..base_length])?;
+    let mut text = match pieces.first() {
+        Some(Piece::Text(text)) => text.clone(),
+        _ => String::new(),
+    };
+    text.push_str(&"*".repeat(stars));
+    Ok(quote!(::code_generator::Name::new_with_type(#text, ::code_generator::NameType::Bypass)))
+}
+
+/// Splits tokens at every occurrence of a punctuation character
+fn split(tokens: &[Token], c: char) -> Vec<&[Token]> {
+    tokens.split(|token| token.is_punct(c)).collect()
+}
+
+/// Parses the statements of a template into generator expressions
+fn statements(tokens: &[Token], span: Span) -> syn::Result<Vec<TokenStream>> {
+    let mut parsed = Vec::new();
+    let mut index = 0;
+    while index < tokens.len() {
+        let (statement, length) = statement(&tokens[index..], span)?;
+        parsed.push(statement);
+        index += length;
+    }
+    Ok(parsed)
+}
+
+/// Generates a vector of boxed generators for the statements of a block
+fn boxed_statements(tokens: &[Token], span: Span) -> syn::Result<TokenStream> {
+    let statements = statements(tokens, span)?;
+    Ok(quote!(::std::vec![#(::std::boxed::Box::new(#statements)),*]))
+}
+
+/// Parses one statement, returning its generator expression and the number
+/// of tokens it is made of
+fn statement(tokens: &[Token], span: Span) -> syn::Result<(TokenStream, usize)> {
+    let first = &tokens[0];
+
+    if let Token::Comment(comment, _) = first {
+        return Ok((quote!(::std::string::String::from(#comment)), 1));
+    }
+
+    if let Token::Directive(directive) = first {
+        return include(directive, tokens);
+    }
+
+    if let Some(keyword) = UNSUPPORTED_STATEMENTS.iter().find(|keyword| first.is_ident(keyword)) {
+        return Err(syn::Error::new(first.span(), format!("`{}` is not supported, insert it with a #variable", keyword)));
+    }
+
+    if first.is_ident("if") || first.is_ident("while") {
+        let (condition, body) = match (tokens.get(1), tokens.get(2)) {
+            (Some(condition), Some(body)) => (
+                condition.group(Delimiter::Parenthesis),
+                body.group(Delimiter::Brace)
+            ),
+            _ => (None, None),
+        };
+        let (condition, body) = match (condition, body) {
+            (Some(condition), Some(body)) => (condition, body),
+            _ => return Err(syn::Error::new(first.span(), "expected a condition in parentheses and a body in braces")),
+        };
+        if tokens.get(3).is_some_and(|token| token.is_ident("else")) {
+            return Err(syn::Error::new(tokens[3].span(), "else is not supported by IfStatement"));
+        }
+        let condition = code_expr(condition)?;
+        let body = boxed_statements(body, span)?;
+        let statement = if first.is_ident("if") {
+            quote!(::code_generator::IfStatement::new(#condition, ::code_generator::CodeBody::new(#body)))
+        } else {
+            quote!(::code_generator::WhileStatement::new(#condition, ::code_generator::CodeBody::new(#body)))
+        };
+        return Ok((statement, 3));
+    }
+
+    if first.is_ident("for") {
+        let header = tokens.get(1).and_then(|token| token.group(Delimiter::Parenthesis));
+        let body = tokens.get(2).and_then(|token| token.group(Delimiter::Brace));
+        let (header, body) = match (header, body) {
+            (Some(header), Some(body)) => (header, body),
+            _ => return Err(syn::Error::new(first.span(), "expected a loop header in parentheses and a body in braces")),
+        };
+        let clauses = split(header, ';');
+        if clauses.len() != 3 {
+            return Err(syn::Error::new(tokens[1].span(), "expected three clauses separated by `;`"));
+        }
+        let (init, condition, update) = (code_expr(clauses[0])?, code_expr(clauses[1])?, code_expr(clauses[2])?);
+        let body = boxed_statements(body, span)?;
+        return Ok((quote!(::code_generator::ForLoop::new(#init, #condition, #update, #body)), 3));
+    }
+
+    if first.is_ident("struct") || first.is_ident("enum") {
+        if let (Some(name), Some(body)) = (tokens.get(1), tokens.get(2).and_then(|token| token.group(Delimiter::Brace))) {
+            let name = name_expr(std::slice::from_ref(name), first.span(), "name")?;
+            let length = if tokens.get(3).is_some_and(|token| token.is_punct(';')) { 4 } else { 3 };
+            let statement = if first.is_ident("struct") {
+                let members = split(body, ';').into_iter()
+                    .filter(|member| !member.is_empty())
+                    .map(|member| declaration_expr(member, first.span()))
+                    .collect::<syn::Result<Vec<_>>>()?;
+                quote!(::code_generator::Struct::new(#name, ::std::vec![#(#members),*]))
+            } else {
+                let values = split(body, ',').into_iter()
+                    .filter(|value| !value.is_empty())
+                    .map(enum_value_expr)
+                    .collect::<syn::Result<Vec<_>>>()?;
+                quote!(::code_generator::Enum::new(#name, ::std::vec![#(#values),*]))
+            };
+            return Ok((statement, length));
+        }
+    }
+
+    if let Some(function) = function(tokens, span)? {
+        return Ok(function);
+    }
+
+    // An interpolation standing alone inserts the generator itself
+    if let Token::Interpolation(ident) = first {
+        if !matches!(tokens.get(1), Some(Token::Punct(..)) | Some(Token::Group(Delimiter::Parenthesis, _, _))) {
+            return Ok((quote!(::std::clone::Clone::clone(&#ident)), 1));
+        }
+    }
+
+    let end = match tokens.iter().position(|token| token.is_punct(';')) {
+        Some(end) => end,
+        None => return Err(syn::Error::new(tokens[tokens.len() - 1].span(), "expected `;` after the statement")),
+    };
+    let block = (0..end).find(|index| tokens[*index].group(Delimiter::Brace).is_some() && !is_initializer(tokens, *index));
+    if let Some(block) = block {
+        return Err(syn::Error::new(
+            tokens[block].span(),
+            "a block is only supported as an initializer or the body of a function, struct, enum, if, while or for"
+        ));
+    }
+    Ok((code_expr(&tokens[..=end])?, end + 1))
+}
+
+/// Whether the braces at an index are an initializer, after `=` or the type
+/// of a compound literal
+fn is_initializer(tokens: &[Token], index: usize) -> bool {
+    let before = |offset: usize| index.checked_sub(offset).map(|index| &tokens[index]);
+    match before(1) {
+        Some(token) if token.is_punct('=') => true,
+        Some(token) if token.group(Delimiter::Parenthesis).is_some() => {
+            matches!(before(2), Some(Token::Punct(..))) || before(2).is_some_and(|token| token.is_ident("return"))
+        }
+        _ => false,
+    }
+}
+
+/// Parses an `#include`, the only directive which can be told apart from the
+/// code after it without line breaks
+fn include(directive: &Ident, tokens: &[Token]) -> syn::Result<(TokenStream, usize)> {
+    if directive != "include" {
+        return Err(syn::Error::new(
+            directive.span(),
+            format!("`#{}` is not supported, its end cannot be found without line breaks", directive)
+        ));
+    }
+
+    if let Some(Token::Literal(literal)) = tokens.get(1) {
+        let file = syn::parse2::<syn::LitStr>(TokenTree::Literal(literal.clone()).into())?.value();
+        return match file.strip_suffix(".h") {
+            Some(file) => Ok((quote!(::code_generator::Include::new(
+                ::code_generator::Name::new_with_type(#file, ::code_generator::NameType::Bypass)
+            )), 2)),
+            None => Err(syn::Error::new(literal.span(), "expected a header file ending in `.h`")),
+        };
+    }
+
+    if tokens.get(1).is_some_and(|token| token.is_punct('<')) {
+        if let Some(end) = tokens.iter().position(|token| token.is_punct('>')) {
+            let mut file = String::new();
+            for token in &tokens[2..end] {
+                match token {
+                    Token::Ident(ident) => file.push_str(&ident.to_string()),
+                    Token::Literal(literal) => file.push_str(&literal.to_string()),
+                    Token::Punct(c, _, _) => file.push(*c),
+                    _ => return Err(syn::Error::new(token.span(), "expected a file name")),
+                }
+            }
+            return Ok((quote!(::code_generator::Include::new_sys(#file)), end + 1));
+        }
+    }
+    Err(syn::Error::new(directive.span(), "expected `<file.h>` or `\"file.h\"` after `#include`"))
+}
+
+/// Parses a function definition or declaration, if the tokens start with one
+fn function(tokens: &[Token], span: Span) -> syn::Result<Option<(TokenStream, usize)>> {
+    if SPACED_KEYWORDS.iter().any(|keyword| tokens[0].is_ident(keyword)) {
+        return Ok(None);
+    }
+    let parameters_index = match tokens.iter().position(|token| matches!(token, Token::Group(..) | Token::Punct(..) if !token.is_punct('*'))) {
+        Some(index) if index >= 2 => index,
+        _ => return Ok(None),
+    };
+    let parameters = match tokens[parameters_index].group(Delimiter::Parenthesis) {
+        Some(parameters) => parameters,
+        None => return Ok(None),
+    };
+    let next = tokens.get(parameters_index + 1);
+    let body = next.and_then(|token| token.group(Delimiter::Brace));
+    let is_declaration = next.is_some_and(|token| token.is_punct(';'));
+    if body.is_none() && !is_declaration {
+        return Ok(None);
+    }
+
+    let return_type = type_expr(&tokens[..parameters_index - 1], span)?;
+    let name = name_expr(&tokens[parameters_index - 1..parameters_index], span, "function name")?;
+    let is_void = matches!(parameters, [void] if void.is_ident("void"));
+    let parameters = match parameters {
+        [] => Vec::new(),
+        _ if is_void => Vec::new(),
+        _ => split(parameters, ',').into_iter()
+            .map(|parameter| declaration_expr(parameter, tokens[parameters_index].span()))
+            .collect::<syn::Result<Vec<_>>>()?,
+    };
+
+    let mut signature = quote!(::code_generator::FunctionSignature::new(#return_type, #name, ::std::vec![#(#parameters),*]));
+    if is_void {
+        // an empty list is `()`, which declares a function without a prototype
+        signature = quote!({
+            let mut signature = #signature;
+            *signature.parameters_mut() = ::code_generator::ParameterList::new(::std::vec![
+                ::std::boxed::Box::new(::std::string::String::from("void"))
+            ]);
+            signature
+        });
+    }
+    let function = match body {
+        Some(body) => {
+            let body = boxed_statements(body, span)?;
+            quote!(::code_generator::Function::new(#signature, ::code_generator::CodeSet::new(#body)))
+        }
+        None => quote!(::code_generator::FunctionDeclaration::from(#signature)),
+    };
+    Ok(Some((function, parameters_index + 2)))
+}
+
+/// Generates a value of an enum like "busy = 4"
+fn enum_value_expr(tokens: &[Token]) -> syn::Result<TokenStream> {
+    let (name, value) = match tokens {
+        [name] => (name, None),
+        [name, equals, value @ ..] if equals.is_punct('=') => (name, Some(value)),
+        _ => return Err(syn::Error::new(tokens[0].span(), "expected an enum value like `name` or `name = 1`")),
+    };
+    let name = name_expr(std::slice::from_ref(name), name.span(), "name")?;
+    let value = match value {
+        None => quote!(::std::option::Option::None),
+        Some(value) => {
+            let value = integer(value).ok_or_else(|| syn::Error::new(
+                tokens[0].span(),
+                "expected an integer literal as the enum value"
+            ))?;
+            quote!(::std::option::Option::Some(#value))
+        }
+    };
+    Ok(quote!((#name, #value)))
+}
+
+/// Parses a C integer literal, with an optional sign and suffix
+fn integer(tokens: &[Token]) -> Option<i64> {
+    let (negative, literal) = match tokens {
+        [Token::Literal(literal)] => (false, literal),
+        [minus, Token::Literal(literal)] if minus.is_punct('-') => (true, literal),
+        _ => return None,
+    };
+    let text = literal.to_string();
+    let text = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let value = if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if text.len() > 1 && text.starts_with('0') {
+        i64::from_str_radix(&text[1..], 8).ok()?
+    } else {
+        text.parse().ok()?
+    };
+    Some(if negative { -value } else { value })
+}
+
+pub(crate) fn expand(input: TokenStream) -> syn::Result<TokenStream> {
+    let tokens = tokenize(input);
+    let mut statements = statements(&tokens, Span::call_site())?;
+    if statements.len() == 1 {
+        return Ok(statements.remove(0));
+    }
+    Ok(quote!(::code_generator::CodeSet::new(::std::vec![#(::std::boxed::Box::new(#statements)),*])))
+}
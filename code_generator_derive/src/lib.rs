@@ -0,0 +1,407 @@
+//! Procedural macros of the code_generator crate
+//!
+//! This crate is used through the `derive` and `macros` features of
+//! code_generator, which re-export the macros.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+mod c_code;
+
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, LitStr, Member};
+
+/// Implements CodeGenerate by generating the fields of a struct in order
+///
+/// Every field which is not skipped must implement CodeGenerate, and the
+/// struct must implement Clone, like every generator. The children of the
+/// generator are its fields, so visitors walk into them.
+///
+/// The generated code is configured with `code` attributes:
+///
+/// * `#[code(separator = "...")]` on the struct writes the text between
+///   consecutive fields. Trailing whitespace of the separator is dropped
+///   before a line break.
+/// * `#[code(new_line)]` starts the field after this one on a new line.
+/// * `#[code(indent)]` generates the field on a new line, one indentation
+///   level deeper.
+/// * `#[code(name_type = Member)]` generates a Name field with the given
+///   NameType, which can also be an expression like
+///   `NameType::FixedCase(CaseType::SnakeCase)`.
+/// * `#[code(skip)]` leaves the field out of the generated code.
+///
+/// ```
+/// # use code_generator::CodeGenerate;
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Name;
+/// # use code_generator_derive::CodeGenerate;
+/// #
+/// #[derive(Clone, CodeGenerate)]
+/// #[code(separator = " ")]
+/// struct Register {
+///     #[code(skip)]
+///     description: String,
+///     prefix: &'static str,
+///     #[code(name_type = ConstDefine)]
+///     name: Name,
+///     address: String,
+/// }
+///
+/// let register = Register {
+///     description: String::from("Control register of the UART"),
+///     prefix: "#define",
+///     name: Name::new("uartCtrl"),
+///     address: String::from("0x40001000"),
+/// };
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+/// assert_eq!("#define UART_CTRL 0x40001000", format!("{}", register.display(&info)));
+/// ```
+///
+/// Nested fields are indented like the body of a code block:
+///
+/// ```
+/// # use code_generator::CaseType;
+/// # use code_generator::CodeGenerate;
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Name;
+/// # use code_generator::NameType;
+/// # use code_generator::NewLineType;
+/// # use code_generator_derive::CodeGenerate;
+/// #
+/// #[derive(Clone, CodeGenerate)]
+/// struct Section(
+///     &'static str,
+///     #[code(indent, name_type = NameType::FixedCase(CaseType::SnakeCase))]
+///     Name,
+///     #[code(indent, new_line)]
+///     String,
+///     &'static str,
+/// );
+///
+/// let section = Section("SECTIONS {", Name::new("textStart"), String::from(".text : { *(.text) }"), "}");
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+///     "SECTIONS {\n    text_start\n    .text : { *(.text) }\n}",
+///     format!("{}", section.display(&info))
+/// );
+/// ```
+#[proc_macro_derive(CodeGenerate, attributes(code))]
+pub fn derive_code_generate(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// Builds generators from a C template
+///
+/// The template is parsed at compile time into the generators of
+/// code_generator, so the brace style, indentation and naming of the code
+/// still follow the CodeGenerationInfo it is displayed with. Functions,
+/// function declarations, structs, enums, if statements, while loops, for
+/// loops and `#include`s become their generators, and any other statement up
+/// to its `;` is kept as text. A template of several items becomes a CodeSet.
+///
+/// Doc comments (`///`, `//!`, `/** */`) are kept before the statement they
+/// are written on, while plain comments never reach the macro. Constructs
+/// without a generator, like `switch`, `do` and directives other than
+/// `#include`, fail to compile; insert them with a #variable instead.
+///
+/// `#name` interpolates a clone of the variable `name`. In the name of a
+/// function, struct, enum, parameter, member or type it must be a Name, which
+/// is cased for its context like any Name. Anywhere else it can be any
+/// CodeGenerate, and standing alone as a statement it inserts the generator
+/// itself. Text written in the template is kept as it is.
+///
+/// ```
+/// # use code_generator::CodeBody;
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Name;
+/// # use code_generator::NewLineType;
+/// # use code_generator_derive::c_code;
+/// #
+/// let name = Name::new("uartInit");
+/// let baud = Name::new("baudRate");
+/// let setup = c_code! {
+///     clockEnable(CLOCK_UART);
+///     UART->BAUD = #baud;
+/// };
+/// let code = c_code! {
+///     void #name(uint32_t #baud) {
+///         if (!initialized) {
+///             #setup
+///         }
+///         for (int i = 0; i < 4; i++) {
+///             fifo[i] = 0;
+///         }
+///     }
+/// };
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "void uart_init(uint32_t baud_rate) {
+///     if (!initialized) {
+///         clockEnable(CLOCK_UART);
+///         UART->BAUD = baud_rate;
+///     }
+///     for (int i = 0; i < 4; i++) {
+///         fifo[i] = 0;
+///     }
+/// }", format!("{}", code.display(&info)));
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::Allman).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "void uart_init(uint32_t baud_rate)
+/// {
+///     if (!initialized)
+///     {
+///         clockEnable(CLOCK_UART);
+///         UART->BAUD = baud_rate;
+///     }
+///     for (int i = 0; i < 4; i++)
+///     {
+///         fifo[i] = 0;
+///     }
+/// }", format!("{}", code.display(&info)));
+/// ```
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Name;
+/// # use code_generator::NewLineType;
+/// # use code_generator_derive::c_code;
+/// #
+/// let state = Name::new("uartState");
+/// let code = c_code! {
+///     #include <stdint.h>
+///     #include "uart.h"
+///     /// Starts sending the buffer
+///     void uartStart(void);
+///     enum #state { idle, busy = 0x4 };
+///     struct UartConfig { uint32_t baud; const uint8_t *buffer; };
+/// };
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "#include <stdint.h>
+/// #include \"uart.h\"
+/// /// Starts sending the buffer
+/// void uartStart(void);
+/// typedef enum {
+///     idle,
+///     busy = 4,
+/// } UartState;
+/// typedef struct {
+///     uint32_t baud;
+///     const uint8_t* buffer;
+/// } UartConfig;", format!("{}", code.display(&info)));
+/// ```
+///
+/// ```compile_fail
+/// # use code_generator_derive::c_code;
+/// let code = c_code! {
+///     switch (mode) { case 0: break; }
+///     reset();
+/// };
+/// ```
+///
+/// ```compile_fail
+/// # use code_generator_derive::c_code;
+/// let code = c_code! {
+///     #define BUFFER_SIZE 64
+/// };
+/// ```
+///
+/// ```compile_fail
+/// # use code_generator_derive::c_code;
+/// let code = c_code! {
+///     lock() { count++; }
+///     unlock();
+/// };
+/// ```
+#[proc_macro]
+pub fn c_code(input: TokenStream) -> TokenStream {
+    c_code::expand(input.into()).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// The options of a field, from its code attributes
+struct FieldOptions {
+    member: Member,
+    new_line: bool,
+    indent: bool,
+    name_type: Option<Expr>,
+}
+
+/// Parses the separator of the struct from its code attributes
+fn parse_separator(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut separator = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("code")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("separator") {
+                separator = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported code attribute, expected `separator`"))
+            }
+        })?;
+    }
+    Ok(separator)
+}
+
+/// Parses the options of every field which is not skipped
+fn parse_fields(fields: &Fields) -> syn::Result<Vec<FieldOptions>> {
+    let mut options = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let mut field_options = FieldOptions { member, new_line: false, indent: false, name_type: None };
+        let mut skip = false;
+
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("code")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                } else if meta.path.is_ident("new_line") {
+                    field_options.new_line = true;
+                } else if meta.path.is_ident("indent") {
+                    field_options.indent = true;
+                } else if meta.path.is_ident("name_type") {
+                    field_options.name_type = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error(
+                        "unsupported code attribute, expected `skip`, `new_line`, `indent` or `name_type`"
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        if !skip {
+            options.push(field_options);
+        }
+    }
+    Ok(options)
+}
+
+/// Turns a name_type value into a NameType expression, so that a variant can
+/// be named without its enum
+fn name_type_expr(name_type: &Expr) -> TokenStream2 {
+    match name_type {
+        Expr::Path(path) if path.qself.is_none() && path.path.get_ident().is_some() =>
+            quote!(::code_generator::NameType::#path),
+        _ => quote!(#name_type),
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new_spanned(
+            &input.ident,
+            "CodeGenerate can only be derived for structs"
+        )),
+    };
+    let separator = parse_separator(&input)?;
+    let fields = parse_fields(fields)?;
+
+    let mut steps = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let member = &field.member;
+        let field_info = if field.indent {
+            steps.push(quote! {
+                result = result.and(::code_generator::CodeGenerate::generate(&::code_generator::NewLine::new(), f, info));
+                result = result.and(::code_generator::CodeGenerate::generate(&::code_generator::Indentation::new(), f, &info.indent()));
+            });
+            quote!(&info.indent())
+        } else {
+            quote!(info)
+        };
+        steps.push(match &field.name_type {
+            Some(name_type) => {
+                let name_type = name_type_expr(name_type);
+                quote! {
+                    result = result.and(::code_generator::CodeGenerate::generate(
+                        &::code_generator::Name::clone(&self.#member).with_type(#name_type),
+                        f,
+                        #field_info
+                    ));
+                }
+            }
+            None => quote! {
+                result = result.and(::code_generator::CodeGenerate::generate(&self.#member, f, #field_info));
+            },
+        });
+
+        if index + 1 < fields.len() {
+            if let Some(separator) = &separator {
+                // A line break follows, so the separator must not leave
+                // trailing whitespace behind.
+                let separator = if field.new_line || fields[index + 1].indent {
+                    separator.value().trim_end().to_string()
+                } else {
+                    separator.value()
+                };
+                if !separator.is_empty() {
+                    steps.push(quote!(result = result.and(f.write_str(#separator));));
+                }
+            }
+            if field.new_line {
+                steps.push(quote! {
+                    result = result.and(::code_generator::CodeGenerate::generate(&::code_generator::NewLine::new(), f, info));
+                    result = result.and(::code_generator::CodeGenerate::generate(&::code_generator::Indentation::new(), f, info));
+                });
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::code_generator::CodeGenerate));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    if fields.is_empty() {
+        return Ok(quote! {
+            impl #impl_generics ::code_generator::CodeGenerate for #ident #ty_generics #where_clause {
+                fn generate(
+                    &self,
+                    _f: &mut ::std::fmt::Formatter<'_>,
+                    _info: &::code_generator::CodeGenerationInfo
+                ) -> ::std::fmt::Result {
+                    ::std::fmt::Result::Ok(())
+                }
+            }
+        });
+    }
+
+    let members: Vec<&Member> = fields.iter().map(|field| &field.member).collect();
+    Ok(quote! {
+        impl #impl_generics ::code_generator::CodeGenerate for #ident #ty_generics #where_clause {
+            fn generate(
+                &self,
+                f: &mut ::std::fmt::Formatter<'_>,
+                info: &::code_generator::CodeGenerationInfo
+            ) -> ::std::fmt::Result {
+                let mut result = ::std::fmt::Result::Ok(());
+                #(#steps)*
+                result
+            }
+
+            fn children(&self) -> ::std::vec::Vec<&dyn ::code_generator::CodeGenerate> {
+                ::std::vec![#(&self.#members as &dyn ::code_generator::CodeGenerate),*]
+            }
+
+            fn children_mut(&mut self) -> ::std::vec::Vec<&mut dyn ::code_generator::CodeGenerate> {
+                ::std::vec![#(&mut self.#members as &mut dyn ::code_generator::CodeGenerate),*]
+            }
+        }
+    })
+}
@@ -0,0 +1,38 @@
+use code_generator::CodeGenerationInfo;
+use code_generator::CodeStyle;
+use code_generator::DisplayExt;
+use code_generator::Name;
+use code_generator::NewLineType;
+use code_generator_derive::CodeGenerate;
+
+#[derive(Clone, CodeGenerate)]
+#[code(separator = ", ")]
+struct Fields {
+    #[code(new_line)]
+    first: &'static str,
+    second: &'static str,
+    #[code(indent)]
+    third: Name,
+}
+
+#[test]
+fn separator_before_line_break_is_trimmed() {
+    let fields = Fields {
+        first: "a",
+        second: "b",
+        third: Name::new("c"),
+    };
+    let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+    assert_eq!("a,\nb,\n    c", format!("{}", fields.display(&info)));
+}
+
+#[derive(Clone, CodeGenerate)]
+#[code(separator = " ")]
+struct Words(#[code(new_line)] &'static str, &'static str, &'static str);
+
+#[test]
+fn blank_separator_before_line_break_is_skipped() {
+    let words = Words("a", "b", "c");
+    let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+    assert_eq!("a\nb c", format!("{}", words.display(&info)));
+}
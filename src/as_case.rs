@@ -1,39 +1,42 @@
-use core::{fmt::Display, str::Chars};
-/// Performance improvement based on [https://internals.rust-lang.org/t/add-as-lowercase-et-al/15797]
+// Performance improvement based on [https://internals.rust-lang.org/t/add-as-lowercase-et-al/15797]
+use core::fmt::Display;
 
-use crate::CaseType;
+use crate::{CaseType, CaseTypes, ChunkCase, DigitBoundary};
 
 pub const CASE_SEPARATOR: char = '`';
 
 pub struct Cased<'a, T: ?Sized> {
-    case_type: CaseType,
+    case_type: &'a CaseType,
+    case_types: &'a CaseTypes,
     source: &'a T,
 }
 
 pub trait AsCase<'a> {
-    fn as_case(&'a self, case_type: CaseType) -> impl Display + 'a;
+    fn as_case(&'a self, case_type: &'a CaseType, case_types: &'a CaseTypes) -> impl Display + 'a;
 }
 
 impl<'a> AsCase<'a> for str {
-    fn as_case(&'a self, case_type: CaseType) -> impl Display + 'a {
+    fn as_case(&'a self, case_type: &'a CaseType, case_types: &'a CaseTypes) -> impl Display + 'a {
         Cased {
             case_type,
+            case_types,
             source: self
         }
     }
 }
 
 impl<'a> AsCase<'a> for String {
-    fn as_case(&self, case_type: CaseType) -> impl Display {
+    fn as_case(&'a self, case_type: &'a CaseType, case_types: &'a CaseTypes) -> impl Display + 'a {
         Cased {
             case_type,
+            case_types,
             source: self
         }
     }
 }
 
 impl<'a, T: ?Sized> Cased<'a, T> {
-    fn write_first_char(case_type: CaseType, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    fn write_first_char(case_type: &CaseType, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match case_type {
             CaseType::FlatCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::ScreamingCase => { write!(f, "{}", char.to_uppercase()) }
@@ -41,9 +44,15 @@ impl<'a, T: ?Sized> Cased<'a, T> {
             CaseType::PascalCase => { write!(f, "{}", char.to_uppercase()) }
             CaseType::SnakeCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::ScreamingSnakeCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::KebabCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::ScreamingKebabCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::TrainCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::AdaCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::DotCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::Custom { first_chunk, .. } => { first_chunk.write_first_char(char, f) }
         }
     }
-    fn write_first_char_in_chunk(case_type: CaseType, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    fn write_first_char_in_chunk(case_type: &CaseType, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match case_type {
             CaseType::FlatCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::ScreamingCase => { write!(f, "{}", char.to_uppercase()) }
@@ -51,9 +60,15 @@ impl<'a, T: ?Sized> Cased<'a, T> {
             CaseType::PascalCase => { write!(f, "{}", char.to_uppercase()) }
             CaseType::SnakeCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::ScreamingSnakeCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::KebabCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::ScreamingKebabCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::TrainCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::AdaCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::DotCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::Custom { other_chunks, .. } => { other_chunks.write_first_char(char, f) }
         }
     }
-    fn write_default_char(case_type: CaseType, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    fn write_default_char(case_type: &CaseType, is_first_chunk: bool, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match case_type {
             CaseType::FlatCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::ScreamingCase => { write!(f, "{}", char.to_uppercase()) }
@@ -61,59 +76,159 @@ impl<'a, T: ?Sized> Cased<'a, T> {
             CaseType::PascalCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::SnakeCase => { write!(f, "{}", char.to_lowercase()) }
             CaseType::ScreamingSnakeCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::KebabCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::ScreamingKebabCase => { write!(f, "{}", char.to_uppercase()) }
+            CaseType::TrainCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::AdaCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::DotCase => { write!(f, "{}", char.to_lowercase()) }
+            CaseType::Custom { first_chunk, other_chunks, .. } => {
+                let chunk_case = if is_first_chunk { first_chunk } else { other_chunks };
+                chunk_case.write_default_char(char, f)
+            }
         }
     }
-    fn write_chunk_separator(case_type: CaseType, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    fn write_chunk_separator(case_type: &CaseType, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match case_type {
             CaseType::FlatCase => { core::fmt::Result::Ok(()) }
             CaseType::ScreamingCase => { core::fmt::Result::Ok(()) }
             CaseType::CamelCase => { core::fmt::Result::Ok(()) }
             CaseType::PascalCase => { core::fmt::Result::Ok(()) }
-            CaseType::SnakeCase => { write!(f, "{}", '_') }
-            CaseType::ScreamingSnakeCase => { write!(f, "{}", '_') }
+            CaseType::SnakeCase => { write!(f, "_") }
+            CaseType::ScreamingSnakeCase => { write!(f, "_") }
+            CaseType::KebabCase => { write!(f, "-") }
+            CaseType::ScreamingKebabCase => { write!(f, "-") }
+            CaseType::TrainCase => { write!(f, "-") }
+            CaseType::AdaCase => { write!(f, "_") }
+            CaseType::DotCase => { write!(f, ".") }
+            CaseType::Custom { separator, .. } => { write!(f, "{}", separator) }
+        }
+    }
+    /// Acronyms keep their uppercase form in chunks which are capitalized
+    fn is_chunk_capitalized(case_type: &CaseType, is_first_chunk: bool) -> bool {
+        match case_type {
+            CaseType::CamelCase => !is_first_chunk,
+            CaseType::PascalCase |
+            CaseType::TrainCase |
+            CaseType::AdaCase => true,
+            CaseType::Custom { first_chunk, other_chunks, .. } => {
+                let chunk_case = if is_first_chunk { first_chunk } else { other_chunks };
+                *chunk_case == ChunkCase::Capitalized
+            }
+            _ => false,
         }
     }
-    fn casify(chars: Chars<'_>, case_type: CaseType, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    fn casify(source: &str, case_type: &CaseType, case_types: &CaseTypes, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let mut result = core::fmt::Result::Ok(());
 
-        let mut is_first_in_chunk = false;
-        let mut is_first = true;
+        for (index, chunk) in split_chunks(source, case_types.digit_boundary).into_iter().enumerate() {
+            if index != 0 {
+                result = result.and(Self::write_chunk_separator(case_type, f));
+            }
 
-        for char in chars {
-            if char == CASE_SEPARATOR {
-                is_first_in_chunk = true;
+            if Self::is_chunk_capitalized(case_type, index == 0) && case_types.is_acronym(chunk) {
+                result = result.and(write!(f, "{}", chunk.to_uppercase()));
                 continue;
             }
 
-            if char.is_uppercase() {
-                is_first_in_chunk = true;
+            let mut chars = chunk.chars();
+            if let Some(char) = chars.next() {
+                if index == 0 {
+                    result = result.and(Self::write_first_char(case_type, char, f));
+                } else {
+                    result = result.and(Self::write_first_char_in_chunk(case_type, char, f));
+                }
             }
+            for char in chars {
+                result = result.and(Self::write_default_char(case_type, index == 0, char, f));
+            }
+        }
 
-            match (is_first, is_first_in_chunk) {
-                (true, _) => {result = result.and(Self::write_first_char(case_type, char, f));}
-                (_, true) => {
-                    result = result.and(Self::write_chunk_separator(case_type, f));
-                    result = result.and(Self::write_first_char_in_chunk(case_type, char, f));
+        result
+    }
+}
+
+/// Splits a name source into its word chunks
+///
+/// A new chunk is started by the CASE_SEPARATOR character (which is dropped)
+/// and by an uppercase character following a non uppercase character. In a
+/// run of uppercase characters, only the last one starts a new chunk, and
+/// only if it is followed by a lowercase character. So "HTTPServer" is split
+/// into "HTTP" and "Server". Digits are split based on the digit boundary.
+/// Empty chunks are skipped.
+pub fn split_chunks(source: &str, digit_boundary: DigitBoundary) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut previous: Option<char> = None;
+    let mut iter = source.char_indices().peekable();
+
+    while let Some((index, char)) = iter.next() {
+        if char == CASE_SEPARATOR {
+            if start < index {
+                chunks.push(&source[start..index]);
+            }
+            start = index + char.len_utf8();
+            previous = None;
+            continue;
+        }
+
+        let next = iter.peek().map(|(_, next)| *next);
+        let is_boundary = match previous {
+            None => false,
+            Some(previous) => {
+                let is_word_start = char.is_uppercase() && (
+                    !previous.is_uppercase() ||
+                    next.is_some_and(|next| next.is_lowercase())
+                );
+                let is_digit_start = char.is_ascii_digit() && !previous.is_ascii_digit();
+                let is_digit_end = !char.is_ascii_digit() && previous.is_ascii_digit();
+
+                match digit_boundary {
+                    DigitBoundary::Attached => is_word_start,
+                    DigitBoundary::Before => is_word_start || is_digit_start,
+                    DigitBoundary::Around => is_word_start || is_digit_start || is_digit_end,
                 }
-                (_, false) => {result = result.and(Self::write_default_char(case_type, char, f));}
             }
+        };
 
-            is_first_in_chunk = false;
-            is_first = false;
+        if is_boundary && start < index {
+            chunks.push(&source[start..index]);
+            start = index;
         }
+        previous = Some(char);
+    }
 
-        result
+    if start < source.len() {
+        chunks.push(&source[start..]);
+    }
+
+    chunks
+}
+
+impl ChunkCase {
+    fn write_first_char(self, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ChunkCase::Lower => { write!(f, "{}", char.to_lowercase()) }
+            ChunkCase::Upper => { write!(f, "{}", char.to_uppercase()) }
+            ChunkCase::Capitalized => { write!(f, "{}", char.to_uppercase()) }
+        }
+    }
+    fn write_default_char(self, char: char, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ChunkCase::Lower => { write!(f, "{}", char.to_lowercase()) }
+            ChunkCase::Upper => { write!(f, "{}", char.to_uppercase()) }
+            ChunkCase::Capitalized => { write!(f, "{}", char.to_lowercase()) }
+        }
     }
 }
 
 impl<'a> Display for Cased<'a, str> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        Self::casify(self.source.chars(), self.case_type, f)
+        Self::casify(self.source, self.case_type, self.case_types, f)
     }
 }
 
 impl<'a> Display for Cased<'a, String> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        Self::casify(self.source.chars(), self.case_type, f)
+        Self::casify(self.source, self.case_type, self.case_types, f)
     }
 }
@@ -0,0 +1,316 @@
+use crate::building_block_generators::*;
+use crate::data_type_generators::*;
+use crate::setup::*;
+
+/// A type given to a builder, as the return type, a parameter or a member
+///
+/// Text is written as it is, since types like `uint32_t` or `const char*`
+/// must not be recased. A Name keeps its own NameType, so
+/// `Name::new("uartConfig")` is still cased as a type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeName(Name);
+
+impl TypeName {
+    pub fn into_name(self) -> Name {
+        self.0
+    }
+}
+
+impl From<&str> for TypeName {
+    fn from(type_name: &str) -> Self {
+        TypeName(Name::new_with_type(type_name, NameType::Bypass))
+    }
+}
+
+impl From<String> for TypeName {
+    fn from(type_name: String) -> Self {
+        TypeName(Name::new_with_type(type_name, NameType::Bypass))
+    }
+}
+
+impl From<Name> for TypeName {
+    fn from(type_name: Name) -> Self {
+        TypeName(type_name)
+    }
+}
+
+/// Builds a Function one part at a time
+///
+/// The return type is `void` unless it is set. Types given as text are
+/// written as they are, see TypeName.
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Function;
+/// # use code_generator::Name;
+/// # use code_generator::NewLineType;
+/// # use code_generator::StorageClass;
+/// #
+/// let function = Function::builder("uartInit")
+///     .returns(Name::new("status"))
+///     .param("const Config*", "config")
+///     .param("uint32_t", "baudRate")
+///     .storage_class(StorageClass::Static)
+///     .comment("Applies the configuration to the UART")
+///     .statement("config->baud = baud_rate;")
+///     .statement("return STATUS_OK;")
+///     .build();
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "/* Applies the configuration to the UART */
+/// static Status uart_init(const Config* config, uint32_t baud_rate) {
+///     config->baud = baud_rate;
+///     return STATUS_OK;
+/// }", format!("{}", function.display(&info)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FunctionBuilder {
+    name: Name,
+    return_type: Name,
+    parameters: Vec<(Name, Name)>,
+    storage_class: Option<StorageClass>,
+    attributes: Vec<String>,
+    comment: Option<String>,
+    statements: Vec<Box<dyn CodeGenerate>>,
+}
+
+impl FunctionBuilder {
+    pub fn new(name: impl Into<Name>) -> FunctionBuilder {
+        FunctionBuilder {
+            name: name.into(),
+            return_type: Name::new_with_type("void", NameType::Bypass),
+            parameters: Vec::new(),
+            storage_class: None,
+            attributes: Vec::new(),
+            comment: None,
+            statements: Vec::new(),
+        }
+    }
+
+    pub fn returns(mut self, return_type: impl Into<TypeName>) -> Self {
+        self.return_type = return_type.into().into_name();
+        self
+    }
+
+    pub fn param(mut self, type_name: impl Into<TypeName>, name: impl Into<Name>) -> Self {
+        self.parameters.push((type_name.into().into_name(), name.into()));
+        self
+    }
+
+    pub fn storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.storage_class = Some(storage_class);
+        self
+    }
+
+    pub fn attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Adds a statement to the end of the body
+    pub fn statement(mut self, statement: impl CodeGenerate + 'static) -> Self {
+        self.statements.push(Box::new(statement));
+        self
+    }
+
+    fn signature(&self) -> FunctionSignature {
+        let mut signature = FunctionSignature::new(
+            self.return_type.clone(),
+            self.name.clone(),
+            self.parameters.clone()
+        );
+        if let Some(storage_class) = self.storage_class {
+            signature = signature.with_storage_class(storage_class);
+        }
+        for attribute in self.attributes.iter() {
+            signature = signature.with_attribute(attribute.clone());
+        }
+        signature
+    }
+
+    /// Builds the declaration of the function, without its comment and body
+    pub fn declaration(&self) -> FunctionDeclaration {
+        FunctionDeclaration::from(self.signature())
+    }
+
+    pub fn build(self) -> Function {
+        let mut function = Function::new(self.signature(), CodeSet::new(self.statements));
+        if let Some(comment) = self.comment {
+            function = function.with_comment(comment);
+        }
+        function
+    }
+}
+
+impl Function {
+    /// Starts building a function, see FunctionBuilder
+    pub fn builder(name: impl Into<Name>) -> FunctionBuilder {
+        FunctionBuilder::new(name)
+    }
+}
+
+/// Builds a Struct one member at a time
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::NewLineType;
+/// # use code_generator::Struct;
+/// #
+/// let value = Struct::builder("uartRegisters")
+///     .member("uint32_t", "control")
+///     .member("uint32_t", "baudRate")
+///     .attribute("__attribute__((packed))")
+///     .comment("Memory mapped registers of the UART")
+///     .build();
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "/* Memory mapped registers of the UART */
+/// typedef struct __attribute__((packed)) {
+///     uint32_t control;
+///     uint32_t baud_rate;
+/// } UartRegisters;", format!("{}", value.display(&info)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct StructBuilder {
+    name: Name,
+    members: Vec<(Name, Name)>,
+    attributes: Vec<String>,
+    comment: Option<String>,
+}
+
+impl StructBuilder {
+    pub fn new(name: impl Into<Name>) -> StructBuilder {
+        StructBuilder {
+            name: name.into(),
+            members: Vec::new(),
+            attributes: Vec::new(),
+            comment: None,
+        }
+    }
+
+    pub fn member(mut self, type_name: impl Into<TypeName>, name: impl Into<Name>) -> Self {
+        self.members.push((type_name.into().into_name(), name.into()));
+        self
+    }
+
+    pub fn attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Struct {
+        let mut value = Struct::new(self.name, self.members);
+        for attribute in self.attributes {
+            value = value.with_attribute(attribute);
+        }
+        if let Some(comment) = self.comment {
+            value = value.with_comment(comment);
+        }
+        value
+    }
+}
+
+impl Struct {
+    /// Starts building a struct, see StructBuilder
+    pub fn builder(name: impl Into<Name>) -> StructBuilder {
+        StructBuilder::new(name)
+    }
+}
+
+/// Builds an Enum one value at a time
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Enum;
+/// # use code_generator::NewLineType;
+/// #
+/// let value = Enum::builder("uartState")
+///     .value("idle")
+///     .numbered_value("busy", 4)
+///     .value("error")
+///     .build();
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "typedef enum {
+///     Idle,
+///     Busy = 4,
+///     Error,
+/// } UartState;", format!("{}", value.display(&info)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnumBuilder {
+    name: Name,
+    values: Vec<(Name, Option<i64>)>,
+    attributes: Vec<String>,
+    comment: Option<String>,
+}
+
+impl EnumBuilder {
+    pub fn new(name: impl Into<Name>) -> EnumBuilder {
+        EnumBuilder {
+            name: name.into(),
+            values: Vec::new(),
+            attributes: Vec::new(),
+            comment: None,
+        }
+    }
+
+    /// Adds a value which follows the value before it
+    pub fn value(mut self, name: impl Into<Name>) -> Self {
+        self.values.push((name.into(), None));
+        self
+    }
+
+    /// Adds a value with an explicit number
+    pub fn numbered_value(mut self, name: impl Into<Name>, value: i64) -> Self {
+        self.values.push((name.into(), Some(value)));
+        self
+    }
+
+    pub fn attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn build(self) -> Enum {
+        let mut value = Enum::new(self.name, self.values);
+        for attribute in self.attributes {
+            value = value.with_attribute(attribute);
+        }
+        if let Some(comment) = self.comment {
+            value = value.with_comment(comment);
+        }
+        value
+    }
+}
+
+impl Enum {
+    /// Starts building an enum, see EnumBuilder
+    pub fn builder(name: impl Into<Name>) -> EnumBuilder {
+        EnumBuilder::new(name)
+    }
+}
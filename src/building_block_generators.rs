@@ -1,6 +1,11 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use crate::setup::*;
-use crate::as_case::AsCase;
+use crate::symbol_table::renamed_symbol;
+use crate::node::{Node, NodeMut};
+use crate::identifier::{apply_identifier_policy, reject, IdentifierError, IdentifierPolicy};
+use crate::as_case::{split_chunks, AsCase, CASE_SEPARATOR};
 
 /// Indentation generator
 /// 
@@ -12,7 +17,8 @@ use crate::as_case::AsCase;
 /// the parent generator. You will only need to use this when you create a
 /// multiline generator which does not use the existing code structure
 /// generators.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Indentation {
 }
 
@@ -29,24 +35,40 @@ impl Indentation {
     /// #
     /// let indent = Indentation::new();
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).indent();
-    /// assert_eq!("    ", format!("{}", indent.display(info)));
+    /// assert_eq!("    ", format!("{}", indent.display(&info)));
     /// ```
     pub fn new() -> Indentation {
         Indentation { }
     }
 }
 
+impl Default for Indentation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CodeGenerate for Indentation {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
-        let indent ;
-        match info.indent_type {
-            IndentationType::Spaces => indent = " ".repeat(info.indent_amount*info.indent_level),
-            IndentationType::Tabs => indent = "	".repeat(
-                ((info.indent_amount+3) / 4) * info.indent_level
-            ),
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        write!(f, "{}", indentation(info, info.indentation_width()))
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::Indentation
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Indentation
+    }
+}
+
+/// The indentation which spans `width` columns
+fn indentation(info: &CodeGenerationInfo, width: usize) -> String {
+    match info.indent_type {
+        IndentationType::Spaces => " ".repeat(width),
+        IndentationType::Tabs | IndentationType::SmartTabs => {
+            "\t".repeat(width / info.effective_tab_width()) + &" ".repeat(width % info.effective_tab_width())
         }
-        
-        write!(f, "{}", indent)
     }
 }
 
@@ -57,8 +79,10 @@ impl CodeGenerate for Indentation {
 /// code generation.
 /// 
 /// The Bypass variant is used to provide a name without having the generator
-/// use any sort of formatting on it.
-#[derive(Clone, Copy)]
+/// use any sort of formatting on it. Only the word separators added by
+/// operations like prepend are dropped.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NameType {
     Default,
     ConstDefine,
@@ -70,7 +94,8 @@ pub enum NameType {
     Bypass,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Name {
     source: String,
     name_type: NameType
@@ -93,22 +118,22 @@ impl Name {
     /// let cc = NameType::FixedCase(CaseType::CamelCase);
     /// let name = Name::new("Test_Name1").with_type(cc);
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("test_Name1", format!("{}", name.display(info)));
+    /// assert_eq!("test_Name1", format!("{}", name.display(&info)));
     ///
     /// let ssc = NameType::FixedCase(CaseType::ScreamingSnakeCase);
     /// let name = Name::new("testName`2").with_type(ssc);
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("TEST_NAME_2", format!("{}", name.display(info)));
+    /// assert_eq!("TEST_NAME_2", format!("{}", name.display(&info)));
     ///
     /// let ssc = NameType::FixedCase(CaseType::SnakeCase);
     /// let name = Name::new("testNameThree").with_type(ssc);
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("test_name_three", format!("{}", name.display(info)));
+    /// assert_eq!("test_name_three", format!("{}", name.display(&info)));
     ///
     /// let ssc = NameType::FixedCase(CaseType::FlatCase);
     /// let name = Name::new("testFour").with_type(ssc);
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("testfour", format!("{}", name.display(info)));
+    /// assert_eq!("testfour", format!("{}", name.display(&info)));
     /// ```
     pub fn new(name: impl Into<String>) -> Name {
         Name {
@@ -151,37 +176,328 @@ impl Name {
     ///     .with_case_types(
     ///         CaseTypes::new().with_const_define(CaseType::ScreamingSnakeCase)
     ///     );
-    /// assert_eq!("TEST_NAME1_H", format!("{}", name.display(info)));
-    pub fn as_include_guard(mut self) -> Name {
-        self = self.with_type(NameType::ConstDefine);
-        self.source.push('H');
+    /// assert_eq!("TEST_NAME1_H", format!("{}", name.display(&info)));
+    pub fn as_include_guard(self) -> Name {
+        self.with_type(NameType::ConstDefine).append("H")
+    }
+
+    /// Appends a word part to the end of the name
+    ///
+    /// The part is treated as a new word regardless of its capitalization.
+    /// Bypass names are concatenated without any separation.
+    ///
+    /// ```
+    /// # use code_generator::CaseType;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::DisplayExt;
+    /// #
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+    /// let name = Name::new("Uart").append("init");
+    /// assert_eq!("uart_init", format!("{}", name.display(&info)));
+    ///
+    /// let name = name.with_type(NameType::Type);
+    /// assert_eq!("UartInit", format!("{}", name.display(&info)));
+    ///
+    /// let name = Name::new_with_type("uart", NameType::Bypass).append("Init");
+    /// assert_eq!("uartInit", format!("{}", name.display(&info)));
+    /// ```
+    pub fn append(mut self, part: impl Into<String>) -> Name {
+        let part = part.into();
+        if part.is_empty() {
+            return self;
+        }
+        if !self.source.is_empty() && !self.is_bypass() {
+            self.source.push(CASE_SEPARATOR);
+        }
+        self.source.push_str(&part);
+        self
+    }
+
+    /// Prepends a word part to the start of the name
+    ///
+    /// ```
+    /// # use code_generator::CaseType;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::DisplayExt;
+    /// #
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+    /// let name = Name::new_with_type("Init", NameType::Function).prepend("uart");
+    /// assert_eq!("uart_init", format!("{}", name.display(&info)));
+    ///
+    /// let cc = NameType::FixedCase(CaseType::CamelCase);
+    /// let name = Name::new_with_type("init", cc).prepend("uart");
+    /// assert_eq!("uartInit", format!("{}", name.display(&info)));
+    /// ```
+    pub fn prepend(mut self, part: impl Into<String>) -> Name {
+        let mut part = part.into();
+        if part.is_empty() {
+            return self;
+        }
+        if !self.source.is_empty() && !self.is_bypass() {
+            part.push(CASE_SEPARATOR);
+        }
+        self.source.insert_str(0, &part);
         self
     }
 
-    fn get_case_type(&self, info: CaseTypes) -> CaseType {
+    /// Creates a Name from a list of word parts
+    ///
+    /// ```
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::DisplayExt;
+    /// #
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+    /// let name = Name::join(["uart", "rx", "buffer"]).with_type(NameType::ConstDefine);
+    /// assert_eq!("UART_RX_BUFFER", format!("{}", name.display(&info)));
+    /// ```
+    pub fn join<I, S>(parts: I) -> Name
+    where I: IntoIterator<Item = S>,
+        S: Into<String> {
+        parts.into_iter().fold(Name::new(""), |name, part| name.append(part))
+    }
+
+    /// Prepends several word parts, keeping their order
+    ///
+    /// ```
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::DisplayExt;
+    /// #
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+    /// let name = Name::new_with_type("Init", NameType::Function)
+    ///     .with_prefix_parts(["drv", "uart"]);
+    /// assert_eq!("drv_uart_init", format!("{}", name.display(&info)));
+    ///
+    /// let name = Name::new_with_type("Init", NameType::Bypass)
+    ///     .with_prefix_parts(["drv", "uart"]);
+    /// assert_eq!("drvuartInit", format!("{}", name.display(&info)));
+    /// ```
+    pub fn with_prefix_parts<I, S>(self, parts: I) -> Name
+    where I: IntoIterator<Item = S>,
+        S: Into<String> {
+        let prefix = Name::join(parts);
+        self.prepend(prefix.source)
+    }
+
+    /// Resolves the name to its final string using the given case types
+    ///
+    /// This produces the same text the name would generate, without the need
+    /// for a formatter.
+    ///
+    /// ```
+    /// # use code_generator::CaseType;
+    /// # use code_generator::CaseTypes;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// let case_types = CaseTypes::new();
+    /// let name = Name::new_with_type("uartInit", NameType::Function);
+    /// assert_eq!("uart_init", name.resolve(&case_types));
+    ///
+    /// let name = Name::new_with_type("uartInit", NameType::ConstDefine);
+    /// assert_eq!("UART_INIT", name.resolve(&case_types));
+    /// ```
+    ///
+    /// If the identifier policy rejects the name, the cased name is returned
+    /// as is. Use try_resolve to find out why the name was rejected.
+    pub fn resolve(&self, case_types: &CaseTypes) -> String {
+        self.try_resolve(case_types).unwrap_or_else(|_| self.resolve_unchecked(case_types))
+    }
+
+    /// Resolves the name, applying the identifier policy of the case types
+    ///
+    /// File and Bypass names are never validated since they are not
+    /// identifiers of the target language.
+    ///
+    /// ```
+    /// # use code_generator::CaseTypes;
+    /// # use code_generator::IdentifierError;
+    /// # use code_generator::IdentifierPolicy;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// let case_types = CaseTypes::new().with_identifier_policy(IdentifierPolicy::Error);
+    /// let name = Name::new_with_type("Int", NameType::Member);
+    /// assert!(name.try_resolve(&case_types).is_err());
+    ///
+    /// let name = Name::new_with_type("Int", NameType::Type);
+    /// assert_eq!(Ok(String::from("Int")), name.try_resolve(&case_types));
+    ///
+    /// let name = Name::new_with_type("int", NameType::Bypass);
+    /// assert_eq!(Ok(String::from("int")), name.try_resolve(&case_types));
+    /// ```
+    pub fn try_resolve(&self, case_types: &CaseTypes) -> Result<String, IdentifierError> {
+        let resolved = self.resolve_unchecked(case_types);
         match self.name_type {
-            NameType::Default => info.default_case,
-            NameType::ConstDefine => info.const_define_case,
-            NameType::Function => info.function_name_case,
-            NameType::Member => info.member_name_case,
-            NameType::Type => info.type_name_case,
-            NameType::File => info.file_name_case,
+            NameType::Bypass | NameType::File => Ok(resolved),
+            _ => apply_identifier_policy(
+                resolved,
+                case_types.target_language,
+                &case_types.identifier_policy
+            ),
+        }
+    }
+
+    fn resolve_unchecked(&self, case_types: &CaseTypes) -> String {
+        if self.is_bypass() {
+            return self.source.replace(CASE_SEPARATOR, "");
+        }
+        self.source.as_case(self.get_case_type(case_types), case_types).to_string()
+    }
+
+    /// Returns the word parts of the name as they were provided
+    ///
+    /// Digits are kept attached to the word they follow.
+    ///
+    /// ```
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// assert_eq!(vec!["uart", "Rx", "Buffer"], Name::new("uartRx`Buffer").parts());
+    /// assert_eq!(vec!["HTTP", "Server2"], Name::new("HTTPServer2").parts());
+    /// assert_eq!(vec!["uartRx"], Name::new_with_type("uartRx", NameType::Bypass).parts());
+    /// ```
+    pub fn parts(&self) -> Vec<&str> {
+        if self.is_bypass() {
+            return vec![self.source.as_str()];
+        }
+        split_chunks(&self.source, DigitBoundary::Attached)
+    }
+
+    /// Returns the word parts of the name normalized to lowercase
+    ///
+    /// Bypass names are never cased, so their only part is the source with
+    /// the word separators removed, just like their output.
+    /// Equality, ordering and hashing of names are based on these parts and
+    /// the NameType, so "fooBar" and "foo`bar" are considered the same name,
+    /// but not as a type and as a function.
+    ///
+    /// ```
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// assert_eq!(vec!["foo", "bar"], Name::new("FooBar").normalized_parts());
+    /// assert!(Name::new("fooBar") == Name::new("foo`bar"));
+    /// assert!(Name::new("fooBar").with_type(NameType::Type) != Name::new("fooBar"));
+    /// ```
+    pub fn normalized_parts(&self) -> Vec<String> {
+        if self.is_bypass() {
+            return vec![self.source.replace(CASE_SEPARATOR, "")];
+        }
+        self.parts().into_iter().map(|part| part.to_lowercase()).collect()
+    }
+
+    /// Returns the source text of the name
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the name type used to pick the case of the name
+    pub fn name_type(&self) -> &NameType {
+        &self.name_type
+    }
+
+    fn is_bypass(&self) -> bool {
+        matches!(self.name_type, NameType::Bypass)
+    }
+
+    fn get_case_type<'a>(&'a self, info: &'a CaseTypes) -> &'a CaseType {
+        match &self.name_type {
+            NameType::Default => &info.default_case,
+            NameType::ConstDefine => &info.const_define_case,
+            NameType::Function => &info.function_name_case,
+            NameType::Member => &info.member_name_case,
+            NameType::Type => &info.type_name_case,
+            NameType::File => &info.file_name_case,
             NameType::FixedCase(case) => case,
-            NameType::Bypass => info.default_case,
+            NameType::Bypass => &info.default_case,
         }
     }
 }
 
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Name::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Name::new(name)
+    }
+}
+
+impl PartialEq for Name {
+    fn eq(&self, other: &Self) -> bool {
+        self.name_type == other.name_type && self.normalized_parts() == other.normalized_parts()
+    }
+}
+
+impl Eq for Name {}
+
+impl Hash for Name {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized_parts().hash(state);
+        self.name_type.hash(state);
+    }
+}
+
+impl PartialOrd for Name {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Name {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalized_parts().cmp(&other.normalized_parts())
+            .then_with(|| self.name_type.cmp(&other.name_type))
+    }
+}
+
 impl CodeGenerate for Name {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        if let Some(renamed) = renamed_symbol(self) {
+            return renamed.generate(f, info);
+        }
         if let NameType::Bypass = self.name_type {
-            return write!(f, "{}", self.source);
+            return write!(f, "{}", self.resolve_unchecked(&info.case_types));
+        }
+        if info.case_types.identifier_policy != IdentifierPolicy::Allow {
+            return match self.try_resolve(&info.case_types) {
+                Ok(resolved) => write!(f, "{}", resolved),
+                Err(error) => {
+                    reject(error);
+                    Err(fmt::Error)
+                }
+            };
         }
-        let case_type: CaseType = self.get_case_type(info.case_types);
-        write!(f, "{}", self.source.as_case(case_type))
+        let case_type = self.get_case_type(&info.case_types);
+        write!(f, "{}", self.source.as_case(case_type, &info.case_types))
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::Name(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Name(self)
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Include {
     file_name: Name,
     is_sys_inc: bool,
@@ -204,7 +520,7 @@ impl Include {
     /// #
     /// let inc = Include::new(Name::new_with_type("my_testFile", NameType::File));
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("#include \"My_testFile.h\"", format!("{}", inc.display(info)));
+    /// assert_eq!("#include \"My_testFile.h\"", format!("{}", inc.display(&info)));
     /// ```
     pub fn new(file_name: Name) -> Include {
         Include {
@@ -228,7 +544,7 @@ impl Include {
     /// #
     /// let inc = Include::new_sys("my_testFile.h");
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("#include <my_testFile.h>", format!("{}", inc.display(info)));
+    /// assert_eq!("#include <my_testFile.h>", format!("{}", inc.display(&info)));
     /// ```
     pub fn new_sys(file_name: impl Into<String>) -> Include {
         Include {
@@ -239,19 +555,50 @@ impl Include {
             is_sys_inc: true
         }
     }
+
+    pub fn file_name(&self) -> &Name {
+        &self.file_name
+    }
+
+    pub fn file_name_mut(&mut self) -> &mut Name {
+        &mut self.file_name
+    }
+
+    /// Checks if the include uses angle brackets
+    pub fn is_sys_inc(&self) -> bool {
+        self.is_sys_inc
+    }
 }
 
 impl CodeGenerate for Include {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let file_name = render(&self.file_name, info)?;
         match self.is_sys_inc {
-            true => write!(f, "#include <{}>", self.file_name.display(info)),
-            false => write!(f, "#include \"{}.h\"", self.file_name.display(info))
+            true => write!(f, "#include <{}>", file_name),
+            false => write!(f, "#include \"{}.h\"", file_name)
         }
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::Include(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Include(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.file_name]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.file_name]
+    }
 }
 
 /// The NewLine struct allows the generation info to decide the new line format
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewLine {
 }
 
@@ -270,24 +617,40 @@ impl NewLine {
     /// let new_line = NewLine::new();
     /// let mut info = CodeGenerationInfo::new();
     /// info.set_new_line_type(NewLineType::CrNl);
-    /// assert_eq!("\r\n", format!("{}", new_line.display(info)));
+    /// assert_eq!("\r\n", format!("{}", new_line.display(&info)));
     /// ```
     pub fn new() -> NewLine {
         NewLine { }
     }
 }
 
+impl Default for NewLine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CodeGenerate for NewLine {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         match info.new_line_type {
             NewLineType::Cr => write!(f, "\r"),
-            NewLineType::Nl => write!(f, "\n"),
+            NewLineType::Nl => writeln!(f),
             NewLineType::CrNl => write!(f, "\r\n"),
             NewLineType::None => write!(f, ""),
         }
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::NewLine
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::NewLine
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeSet {
     code_set: Vec<Box<dyn CodeGenerate>>,
     is_separated: bool,
@@ -301,30 +664,96 @@ impl CodeSet {
     pub fn new_separated(set: Vec<Box<dyn CodeGenerate>>) -> CodeSet {
         CodeSet { code_set: set, is_separated: true }
     }
+
+    pub fn items(&self) -> &[Box<dyn CodeGenerate>] {
+        &self.code_set
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<Box<dyn CodeGenerate>> {
+        &mut self.code_set
+    }
+
+    /// Checks if the items are separated by an empty line
+    pub fn is_separated(&self) -> bool {
+        self.is_separated
+    }
+}
+
+impl CodeSet {
+    /// Finds the aligned column of every item which is part of a run of
+    /// consecutive alignable items
+    fn alignment_columns(&self, info: &CodeGenerationInfo) -> Vec<Option<usize>> {
+        let widths: Vec<Option<usize>> = self.code_set.iter()
+            .map(|item| if info.align_defines { item.alignment_width(info) } else { None })
+            .collect();
+        let mut columns = vec![None; widths.len()];
+
+        let mut start = 0;
+        while start < widths.len() {
+            let end = widths[start..].iter()
+                .position(|width| width.is_none())
+                .map_or(widths.len(), |offset| start + offset);
+            if start < end {
+                let max_width = widths[start..end].iter().flatten().max().copied().unwrap_or(0);
+                let column = info.alignment_column(max_width);
+                for item_column in columns[start..end].iter_mut() {
+                    *item_column = Some(column);
+                }
+            }
+            start = end + 1;
+        }
+
+        columns
+    }
 }
 
 impl CodeGenerate for CodeSet {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result = fmt::Result::Ok(());
-        let mut iter = self.code_set.iter();
-        if let Some(item) = iter.next() {
-            result = result.and(item.generate(f, info));
+        let columns = self.alignment_columns(info);
+        let item_info = |index: usize| {
+            let mut item_info = info.clone();
+            item_info.align_column = columns[index];
+            item_info
+        };
 
-            for item in iter {
+        let mut iter = self.code_set.iter().enumerate();
+        if let Some((index, item)) = iter.next() {
+            result = result.and(item.generate(f, &item_info(index)));
+
+            for (index, item) in iter {
                 result = result.and(NewLine::new().generate(f, info));
                 if self.is_separated {
                     result = result.and(NewLine::new().generate(f, info));
                 }
                 result = result.and(Indentation::new().generate(f, info));
-                result = result.and(item.generate(f, info));
+                result = result.and(item.generate(f, &item_info(index)));
             }
         }
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::CodeSet(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::CodeSet(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        boxed_children(&self.code_set)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        boxed_children_mut(&mut self.code_set)
+    }
 }
 
 /// The JoinedCode struct joins multiple sections of code with no further
 /// formatting, or configuration done outside, inside, or between units.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JoinedCode {
     code_set: Vec<Box<dyn CodeGenerate>>,
 }
@@ -347,15 +776,23 @@ impl JoinedCode {
     ///     Box::new(String::from("Joined"))
     /// ]);
     /// let mut info = CodeGenerationInfo::new();
-    /// assert_eq!("This:Is:Joined", format!("{}", joined.display(info)));
+    /// assert_eq!("This:Is:Joined", format!("{}", joined.display(&info)));
     /// ```
     pub fn new(set: Vec<Box<dyn CodeGenerate>>) -> JoinedCode {
         JoinedCode { code_set: set }
     }
+
+    pub fn items(&self) -> &[Box<dyn CodeGenerate>] {
+        &self.code_set
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<Box<dyn CodeGenerate>> {
+        &mut self.code_set
+    }
 }
 
 impl CodeGenerate for JoinedCode {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result = fmt::Result::Ok(());
 
         for item in self.code_set.iter() {
@@ -364,6 +801,22 @@ impl CodeGenerate for JoinedCode {
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::JoinedCode(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::JoinedCode(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        boxed_children(&self.code_set)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        boxed_children_mut(&mut self.code_set)
+    }
 }
 
 /// Creates a JoinedCode generator
@@ -384,7 +837,7 @@ impl CodeGenerate for JoinedCode {
 /// );
 ///
 /// let mut info = CodeGenerationInfo::new();
-/// assert_eq!("ThisIsJoined", format!("{}", JoinedCode::new(joined).display(info)));
+/// assert_eq!("ThisIsJoined", format!("{}", JoinedCode::new(joined).display(&info)));
 /// ```
 #[macro_export]
 macro_rules! join_code {
@@ -400,7 +853,7 @@ macro_rules! join_code {
 /// Raw code with no formatting besides injecting newlines, and
 /// indentation based on the context
 impl CodeGenerate for String {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result: fmt::Result = fmt::Result::Ok(());
         // First line doesn't print indentation
         let mut iter = self.lines();
@@ -412,7 +865,15 @@ impl CodeGenerate for String {
             result = result.and(Indentation::new().generate(f, info));
             result = result.and(write!(f, "{}", line));
         }
-        return result;
+        result
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::Text(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Text(self)
     }
 }
 
@@ -428,10 +889,10 @@ impl CodeGenerate for String {
 /// let text = "Testing123";
 ///
 /// let mut info = CodeGenerationInfo::new();
-/// assert_eq!("Testing123", format!("{}", text.display(info)));
+/// assert_eq!("Testing123", format!("{}", text.display(&info)));
 /// ```
 impl CodeGenerate for &str {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result: fmt::Result = fmt::Result::Ok(());
         // First line doesn't print indentation
         let mut iter = self.lines();
@@ -443,10 +904,163 @@ impl CodeGenerate for &str {
             result = result.and(Indentation::new().generate(f, info));
             result = result.and(write!(f, "{}", line));
         }
-        return result;
+        result
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::Text(self)
+    }
+}
+
+/// What a TextBlock does with the new lines at the end of its text
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrailingNewLines {
+    #[default]
+    Trim,
+    Keep,
+}
+
+/// A multi-line snippet of text, re-indented to the current indentation
+///
+/// Unlike the String generator, the text is normalized: line endings are
+/// converted to the configured NewLineType, the indentation shared by all
+/// lines is removed before indenting the lines to the current level, and no
+/// line ends with whitespace. A blank first line, as left by a raw string
+/// literal starting on its own line, is dropped.
+///
+/// The indentation left within the text is padded like aligned code, so
+/// with SmartTabs every line is indented with tabs to the current level and
+/// with spaces from there.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextBlock {
+    text: String,
+    trailing_new_lines: TrailingNewLines,
+}
+
+impl TextBlock {
+    /// Creates a TextBlock generator
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::NewLineType;
+    /// # use code_generator::TextBlock;
+    /// # use code_generator::TrailingNewLines;
+    /// #
+    /// let text = TextBlock::new("
+    ///         if (ready) {\r
+    ///             read();  \r
+    ///
+    ///         }
+    /// ");
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+    ///     .with_new_line_type(NewLineType::Nl)
+    ///     .indent();
+    /// assert_eq!(
+    ///     "if (ready) {\n        read();\n\n    }",
+    ///     format!("{}", text.display(&info))
+    /// );
+    ///
+    /// let text = text.with_trailing_new_lines(TrailingNewLines::Keep);
+    /// assert_eq!(
+    ///     "if (ready) {\n        read();\n\n    }\n",
+    ///     format!("{}", text.display(&info))
+    /// );
+    /// ```
+    pub fn new(text: impl Into<String>) -> TextBlock {
+        TextBlock { text: text.into(), trailing_new_lines: TrailingNewLines::Trim }
+    }
+
+    pub fn with_trailing_new_lines(mut self, trailing_new_lines: TrailingNewLines) -> Self {
+        self.trailing_new_lines = trailing_new_lines;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn trailing_new_lines(&self) -> TrailingNewLines {
+        self.trailing_new_lines
+    }
+}
+
+impl CodeGenerate for TextBlock {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let text = self.text.replace("\r\n", "\n").replace('\r', "\n");
+        let mut lines: Vec<&str> = text.split('\n').map(|line| line.trim_end()).collect();
+        if lines.len() > 1 && lines[0].is_empty() {
+            lines.remove(0);
+        }
+        let line_count = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |index| index + 1);
+        // text without any content has no line for new lines to follow
+        let trailing_new_lines = match self.trailing_new_lines {
+            TrailingNewLines::Keep if line_count > 0 => lines.len() - line_count,
+            _ => 0,
+        };
+        lines.truncate(line_count);
+
+        // leading whitespace is measured in columns, so tabs and spaces mix
+        let leading_width = |line: &str| line.chars()
+            .take_while(|char| char.is_whitespace())
+            .fold(0, |width, char| match char {
+                '\t' => (width / info.effective_tab_width() + 1) * info.effective_tab_width(),
+                _ => width + 1,
+            });
+        let common_width = lines.iter()
+            .filter(|line| !line.is_empty())
+            .map(|line| leading_width(line))
+            .min()
+            .unwrap_or(0);
+
+        let mut result = fmt::Result::Ok(());
+        for (index, line) in lines.iter().enumerate() {
+            if index != 0 {
+                result = result.and(NewLine::new().generate(f, info));
+            }
+            if line.is_empty() {
+                continue;
+            }
+            // the first line is indented by the parent generator, and the
+            // indentation within the text is alignment, like a continuation
+            let start = info.indentation_width();
+            if index != 0 {
+                result = result.and(write!(f, "{}", indentation(info, start)));
+            }
+            let relative_width = leading_width(line) - common_width;
+            let padding = info.column_padding(start, start + relative_width);
+            result = result.and(write!(f, "{}{}", padding, line.trim_start()));
+        }
+        for _ in 0..trailing_new_lines {
+            result = result.and(NewLine::new().generate(f, info));
+        }
+
+        result
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::TextBlock(self)
     }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::TextBlock(self)
+    }
+}
+
+/// The children of a generator which holds a list of boxed generators
+pub(crate) fn boxed_children(items: &[Box<dyn CodeGenerate>]) -> Vec<&dyn CodeGenerate> {
+    items.iter().map(|item| item.as_ref() as &dyn CodeGenerate).collect()
+}
+
+pub(crate) fn boxed_children_mut(items: &mut [Box<dyn CodeGenerate>]) -> Vec<&mut dyn CodeGenerate> {
+    items.iter_mut().map(|item| item.as_mut() as &mut dyn CodeGenerate).collect()
 }
 
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SeparatedCode {
     items: Vec<Box<dyn CodeGenerate>>,
     separator: Box<dyn CodeGenerate>,
@@ -455,12 +1069,30 @@ pub struct SeparatedCode {
 
 impl SeparatedCode {
     pub fn new(items: Vec<Box<dyn CodeGenerate>>, separator: Box<dyn CodeGenerate>) -> SeparatedCode {
-        SeparatedCode { items: items, separator: separator }
+        SeparatedCode { items, separator }
+    }
+
+    pub fn items(&self) -> &[Box<dyn CodeGenerate>] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<Box<dyn CodeGenerate>> {
+        &mut self.items
+    }
+
+    pub fn separator(&self) -> &dyn CodeGenerate {
+        self.separator.as_ref()
+    }
+}
+
+impl PartialEq for SeparatedCode {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items && *self.separator == *other.separator
     }
 }
 
 impl CodeGenerate for SeparatedCode {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result: fmt::Result = fmt::Result::Ok(());
 
         let mut iterator = self.items.iter();
@@ -476,8 +1108,341 @@ impl CodeGenerate for SeparatedCode {
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::SeparatedCode(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::SeparatedCode(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        let mut children = boxed_children(&self.items);
+        children.push(self.separator.as_ref());
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        let mut children = boxed_children_mut(&mut self.items);
+        children.push(self.separator.as_mut());
+        children
+    }
+}
+
+/// A binary operation like "a + b"
+///
+/// Whether the operator is surrounded by spaces depends on the spacing
+/// options of the generation info.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BinaryOperation {
+    left: Box<dyn CodeGenerate>,
+    operator: String,
+    right: Box<dyn CodeGenerate>,
+}
+
+impl BinaryOperation {
+    /// Creates a BinaryOperation generator
+    ///
+    /// ```
+    /// # use code_generator::BinaryOperation;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::Spacing;
+    /// #
+    /// let operation = BinaryOperation::new(
+    ///     Name::new_with_type("rxCount", NameType::Member),
+    ///     "<",
+    ///     Name::new_with_type("bufferSize", NameType::ConstDefine)
+    /// );
+    /// let info = CodeGenerationInfo::new();
+    /// assert_eq!("rx_count < BUFFER_SIZE", format!("{}", operation.display(&info)));
+    ///
+    /// let info = info.with_spacing(Spacing::new().with_around_binary_operators(false));
+    /// assert_eq!("rx_count<BUFFER_SIZE", format!("{}", operation.display(&info)));
+    /// ```
+    pub fn new<LT, RT>(left: LT, operator: impl Into<String>, right: RT) -> BinaryOperation
+    where LT: CodeGenerate + 'static,
+        RT: CodeGenerate + 'static {
+        BinaryOperation {
+            left: Box::new(left),
+            operator: operator.into(),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn left(&self) -> &dyn CodeGenerate {
+        self.left.as_ref()
+    }
+
+    pub fn operator(&self) -> &str {
+        &self.operator
+    }
+
+    pub fn right(&self) -> &dyn CodeGenerate {
+        self.right.as_ref()
+    }
+}
+
+impl PartialEq for BinaryOperation {
+    fn eq(&self, other: &Self) -> bool {
+        *self.left == *other.left && self.operator == other.operator && *self.right == *other.right
+    }
+}
+
+impl CodeGenerate for BinaryOperation {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let space = if info.spacing.around_binary_operators { " " } else { "" };
+        let mut result = self.left.generate(f, info);
+        result = result.and(write!(f, "{}{}{}", space, self.operator, space));
+        result = result.and(self.right.generate(f, info));
+
+        result
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::BinaryOperation(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::BinaryOperation(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![self.left.as_ref(), self.right.as_ref()]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![self.left.as_mut(), self.right.as_mut()]
+    }
+}
+
+/// A parenthesized, comma separated list of parameters or arguments
+///
+/// The list is generated on one line unless the line would be longer than
+/// the max_line_width of the generation info, in which case it is wrapped
+/// using the list_wrap_style. Since generators do not know which column they
+/// start at, the list is generated with generate_at_column by generators that
+/// put it after other code on the same line.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterList {
+    items: Vec<Box<dyn CodeGenerate>>,
 }
 
+impl ParameterList {
+    /// Creates a ParameterList generator
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::ListWrapStyle;
+    /// # use code_generator::NewLineType;
+    /// # use code_generator::ParameterList;
+    /// #
+    /// let list = ParameterList::new(vec![
+    ///     Box::new(String::from("first")),
+    ///     Box::new(String::from("second")),
+    ///     Box::new(String::from("third")),
+    /// ]);
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+    ///     .with_new_line_type(NewLineType::Nl);
+    /// assert_eq!("(first, second, third)", format!("{}", list.display(&info)));
+    ///
+    /// let info = info.with_max_line_width(16);
+    /// assert_eq!(
+    ///     "(\n    first,\n    second,\n    third\n)",
+    ///     format!("{}", list.display(&info))
+    /// );
+    ///
+    /// let info = info.with_list_wrap_style(ListWrapStyle::AlignToParen);
+    /// assert_eq!(
+    ///     "(first,\n second,\n third)",
+    ///     format!("{}", list.display(&info))
+    /// );
+    ///
+    /// let info = info.with_list_wrap_style(ListWrapStyle::HangingIndent);
+    /// assert_eq!(
+    ///     "(\n    first, second,\n    third)",
+    ///     format!("{}", list.display(&info.clone().with_max_line_width(20)))
+    /// );
+    /// ```
+    pub fn new(items: Vec<Box<dyn CodeGenerate>>) -> ParameterList {
+        ParameterList { items }
+    }
+
+    pub fn items(&self) -> &[Box<dyn CodeGenerate>] {
+        &self.items
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<Box<dyn CodeGenerate>> {
+        &mut self.items
+    }
+
+    /// Generates the list as if `column` characters were already written on
+    /// the line after the indentation
+    ///
+    /// `suffix_width` is the width of what is written after the list on the
+    /// same line, like the `;` of a declaration or the ` {` of a KnR function,
+    /// so the line including it fits in the max line width. The space inside
+    /// the parentheses is only written next to an item, never at the end or
+    /// start of a line.
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::ListWrapStyle;
+    /// # use code_generator::NewLineType;
+    /// # use code_generator::ParameterList;
+    /// # use code_generator::Spacing;
+    /// # use code_generator::CodeGenerate;
+    /// # use std::fmt;
+    /// #
+    /// struct Call<'a>(&'a ParameterList, &'a CodeGenerationInfo);
+    /// impl fmt::Display for Call<'_> {
+    ///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    ///         write!(f, "call")?;
+    ///         self.0.generate_at_column(f, self.1, 4, 1)?;
+    ///         write!(f, ";")
+    ///     }
+    /// }
+    ///
+    /// let list = ParameterList::new(vec![Box::new("first"), Box::new("second")]);
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+    ///     .with_new_line_type(NewLineType::Nl)
+    ///     .with_list_wrap_style(ListWrapStyle::AlignToParen)
+    ///     .with_spacing(Spacing::new().with_inside_parens(true))
+    ///     .with_max_line_width(22);
+    /// assert_eq!("call( first, second );", Call(&list, &info).to_string());
+    ///
+    /// let info = info.with_max_line_width(21);
+    /// assert_eq!("call( first,\n      second );", Call(&list, &info).to_string());
+    /// ```
+    pub fn generate_at_column(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        info: &CodeGenerationInfo,
+        column: usize,
+        suffix_width: usize
+    ) -> fmt::Result {
+        let items = self.items.iter()
+            .map(|item| render(item.as_ref(), info))
+            .collect::<Result<Vec<String>, fmt::Error>>()?;
+        let inside_parens = info.spacing.inside_parens_space();
+        let single_line_width = items.iter().map(|item| item.chars().count()).sum::<usize>()
+            + items.len().saturating_sub(1) * 2
+            + inside_parens.len() * 2
+            + 2;
+
+        let fits = match info.max_line_width {
+            None => true,
+            Some(max_line_width) => {
+                info.indentation_width() + column + single_line_width + suffix_width <= max_line_width
+            }
+        };
+
+        if items.is_empty() {
+            return write!(f, "()");
+        }
+        if fits {
+            return write!(f, "({}{}{})", inside_parens, items.join(", "), inside_parens);
+        }
+
+        let mut result = fmt::Result::Ok(());
+        match info.list_wrap_style {
+            ListWrapStyle::OnePerLine => {
+                result = result.and(write!(f, "("));
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        result = result.and(write!(f, ","));
+                    }
+                    result = result.and(NewLine::new().generate(f, info));
+                    result = result.and(Indentation::new().generate(f, &info.indent()));
+                    result = result.and(write!(f, "{}", item));
+                }
+                result = result.and(NewLine::new().generate(f, info));
+                result = result.and(Indentation::new().generate(f, info));
+                result = result.and(write!(f, ")"));
+            }
+            ListWrapStyle::AlignToParen => {
+                result = result.and(write!(f, "({}", inside_parens));
+                for (index, item) in items.iter().enumerate() {
+                    if index != 0 {
+                        result = result.and(write!(f, ","));
+                        result = result.and(NewLine::new().generate(f, info));
+                        result = result.and(Indentation::new().generate(f, info));
+                        let start = info.indentation_width();
+                        let item_column = start + column + 1 + inside_parens.len();
+                        result = result.and(write!(f, "{}", info.column_padding(start, item_column)));
+                    }
+                    result = result.and(write!(f, "{}", item));
+                }
+                result = result.and(write!(f, "{})", inside_parens));
+            }
+            ListWrapStyle::HangingIndent => {
+                let max_line_width = info.max_line_width.unwrap_or(usize::MAX);
+                let start_width = info.indent().indentation_width();
+                let mut line_width = start_width;
+
+                result = result.and(write!(f, "("));
+                result = result.and(NewLine::new().generate(f, info));
+                result = result.and(Indentation::new().generate(f, &info.indent()));
+                for (index, item) in items.iter().enumerate() {
+                    // the `,` after the item, or the `)` and suffix after the last one
+                    let item_width = match index + 1 == items.len() {
+                        true => item.chars().count() + inside_parens.len() + 1 + suffix_width,
+                        false => item.chars().count() + 1,
+                    };
+                    if index != 0 {
+                        result = result.and(write!(f, ","));
+                        if line_width + 1 + item_width > max_line_width {
+                            result = result.and(NewLine::new().generate(f, info));
+                            result = result.and(Indentation::new().generate(f, &info.indent()));
+                            line_width = start_width;
+                        } else {
+                            result = result.and(write!(f, " "));
+                            line_width += 1;
+                        }
+                    }
+                    result = result.and(write!(f, "{}", item));
+                    line_width += item_width;
+                }
+                result = result.and(write!(f, "{})", inside_parens));
+            }
+        }
+
+        result
+    }
+}
+
+impl CodeGenerate for ParameterList {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        self.generate_at_column(f, info, 0, 0)
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::ParameterList(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::ParameterList(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        boxed_children(&self.items)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        boxed_children_mut(&mut self.items)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeBody {
     raw_code: CodeSet,
 }
@@ -498,10 +1463,10 @@ impl CodeBody {
     ///     Box::new(String::from("Body"))
     /// ]);
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("{\r\n    Body\r\n}", format!("{}", code_body.display(info)));
+    /// assert_eq!("{\r\n    Body\r\n}", format!("{}", code_body.display(&info)));
     /// 
     /// let info = CodeGenerationInfo::from_style(CodeStyle::Horstmann);
-    /// assert_eq!("{   Body\r\n}", format!("{}", code_body.display(info)));
+    /// assert_eq!("{   Body\r\n}", format!("{}", code_body.display(&info)));
     /// ```
     pub fn new(code: Vec<Box<dyn CodeGenerate>>) -> CodeBody {
         CodeBody {raw_code: CodeSet::new(code)}
@@ -523,49 +1488,56 @@ impl CodeBody {
     ///     Box::new(String::from("Body"))
     /// ]));
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("{\r\n    Body\r\n}", format!("{}", code_body.display(info)));
+    /// assert_eq!("{\r\n    Body\r\n}", format!("{}", code_body.display(&info)));
     /// 
     /// let info = CodeGenerationInfo::from_style(CodeStyle::Horstmann);
-    /// assert_eq!("{   Body\r\n}", format!("{}", code_body.display(info)));
+    /// assert_eq!("{   Body\r\n}", format!("{}", code_body.display(&info)));
     /// ```
     pub fn from_set(code: CodeSet) -> CodeBody {
         CodeBody { raw_code: code }
     }
+
+    /// The statements of the body
+    pub fn items(&self) -> &[Box<dyn CodeGenerate>] {
+        self.raw_code.items()
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<Box<dyn CodeGenerate>> {
+        self.raw_code.items_mut()
+    }
 }
 
 impl CodeGenerate for CodeBody {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result: fmt::Result = fmt::Result::Ok(());
-        match info.indent_style {
+        match info.current_indent_style() {
             IndentationStyle::Allman => {
                 result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, "{{"));
                 result = result.and(NewLine::new().generate(f, info));
-                result = result.and(Indentation::new().generate(f, info.indent()));
-                result = result.and(self.raw_code.generate(f, info.indent()));
+                result = result.and(Indentation::new().generate(f, &info.indent()));
+                result = result.and(self.raw_code.generate(f, &info.indent()));
                 result = result.and(NewLine::new().generate(f, info));
                 result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, "}}"));
             }
             IndentationStyle::GNU => {
+                let brace_info = gnu_brace_info(info);
                 result = result.and(write!(f, "{{"));
                 result = result.and(NewLine::new().generate(f, info));
-                result = result.and(Indentation::new().generate(f, info.indent().indent()));
-                result = result.and(self.raw_code.generate(f, info.indent().indent()));
+                result = result.and(Indentation::new().generate(f, &brace_info.indent()));
+                result = result.and(self.raw_code.generate(f, &brace_info.indent()));
                 result = result.and(NewLine::new().generate(f, info));
-                if info.context != GeneratorContext::Function {
-                    result = result.and(Indentation::new().generate(f, info.indent()));
-                }
+                result = result.and(Indentation::new().generate(f, &brace_info));
                 result = result.and(write!(f, "}}"));
             }
             IndentationStyle::Horstmann => {
                 result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, "{{"));
-                let mut temp_info = info;
-                temp_info.indent_level = 1;
-                temp_info.indent_amount -= 1;// to account for '{' if using spaces
-                result = result.and(Indentation::new().generate(f, temp_info));
-                result = result.and(self.raw_code.generate(f, info.indent()));
+                // the body starts on the line of the '{'
+                let start = info.indentation_width() + 1;
+                result = result.and(write!(f, "{}", info.column_padding(start, info.indent().indentation_width())));
+                result = result.and(self.raw_code.generate(f, &info.indent()));
                 result = result.and(NewLine::new().generate(f, info));
                 result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, "}}"));
@@ -573,8 +1545,8 @@ impl CodeGenerate for CodeBody {
             IndentationStyle::KnR => {
                 result = result.and(write!(f, "{{"));
                 result = result.and(NewLine::new().generate(f, info));
-                result = result.and(Indentation::new().generate(f, info.indent()));
-                result = result.and(self.raw_code.generate(f, info.indent()));
+                result = result.and(Indentation::new().generate(f, &info.indent()));
+                result = result.and(self.raw_code.generate(f, &info.indent()));
                 result = result.and(NewLine::new().generate(f, info));
                 result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, "}}"));
@@ -582,17 +1554,16 @@ impl CodeGenerate for CodeBody {
             IndentationStyle::Pico => {
                 result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, "{{"));
-                let mut temp_info = info;
-                temp_info.indent_level = 1;
-                temp_info.indent_amount -= 1;// to account for '{' if using spaces
-                result = result.and(Indentation::new().generate(f, temp_info));
-                result = result.and(self.raw_code.generate(f, info.indent()));
+                // the body starts on the line of the '{'
+                let start = info.indentation_width() + 1;
+                result = result.and(write!(f, "{}", info.column_padding(start, info.indent().indentation_width())));
+                result = result.and(self.raw_code.generate(f, &info.indent()));
                 //result = result.and(Indentation::new().generate(f, info));
                 result = result.and(write!(f, " }}"));
             }
             IndentationStyle::None => {
                 result = result.and(write!(f, "{{"));
-                result = result.and(self.raw_code.generate(f, info.indent()));
+                result = result.and(self.raw_code.generate(f, &info.indent()));
                 result = result.and(write!(f, "}}"));
             }
             _ => result = result.and(write!(f, "NOT SUPPORTED YET!")),
@@ -600,8 +1571,42 @@ impl CodeGenerate for CodeBody {
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::CodeBody(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::CodeBody(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        self.raw_code.children()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        self.raw_code.children_mut()
+    }
 }
 
+/// The generation info the braces of a GNU style body are indented with
+///
+/// GNU style indents the braces of statement bodies by one level, but not
+/// those of declarations (unless they are declared inside a function).
+fn gnu_brace_info(info: &CodeGenerationInfo) -> CodeGenerationInfo {
+    let is_declaration = matches!(
+        info.context.current(),
+        GeneratorContext::Function | GeneratorContext::Struct | GeneratorContext::Enum
+    );
+    if is_declaration && !info.context.parents().any(|context| context == GeneratorContext::Function) {
+        info.clone()
+    } else {
+        info.indent()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeaderPlusBody<HT> {
     header: HT,
     body: CodeBody,
@@ -627,26 +1632,47 @@ impl<HT> HeaderPlusBody<HT> {
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
     /// assert_eq!(
     ///     "header {\r\n    Body\r\n}",
-    ///     format!("{}", header_plus_body.display(info))
+    ///     format!("{}", header_plus_body.display(&info))
     /// );
     /// 
     /// let info = CodeGenerationInfo::from_style(CodeStyle::Allman);
     /// assert_eq!(
     ///     "header\r\n{\r\n    Body\r\n}",
-    ///     format!("{}", header_plus_body.display(info))
+    ///     format!("{}", header_plus_body.display(&info))
     /// );
     /// ```
     pub fn new(header: HT, body: CodeBody) -> HeaderPlusBody<HT>{
-        HeaderPlusBody {header: header, body: body}
+        HeaderPlusBody { header, body }
     }
-}
 
-impl<HT> CodeGenerate for HeaderPlusBody<HT>
-where HT: CodeGenerate,{
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    pub fn header(&self) -> &HT {
+        &self.header
+    }
+
+    pub fn header_mut(&mut self) -> &mut HT {
+        &mut self.header
+    }
+
+    pub fn body(&self) -> &CodeBody {
+        &self.body
+    }
+
+    pub fn body_mut(&mut self) -> &mut CodeBody {
+        &mut self.body
+    }
+
+    /// Generates the header with the given function, which receives the width
+    /// of the brace written after the header on its line
+    pub(crate) fn generate_with_header<F>(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo, header: F) -> fmt::Result
+    where F: FnOnce(&mut fmt::Formatter<'_>, usize) -> fmt::Result {
+        let brace_width = match info.current_indent_style() {
+            IndentationStyle::KnR => " {".len(),
+            IndentationStyle::None => "{".len(),
+            _ => 0,
+        };
         let mut result: fmt::Result = fmt::Result::Ok(());
-        result = result.and(self.header.generate(f, info));
-        match info.indent_style {
+        result = result.and(header(f, brace_width));
+        match info.current_indent_style() {
             IndentationStyle::Allman |
             IndentationStyle::Horstmann |
             IndentationStyle::Pico => {
@@ -654,9 +1680,7 @@ where HT: CodeGenerate,{
             },
             IndentationStyle::GNU => {
                 result = result.and(NewLine::new().generate(f, info));
-                if info.context != GeneratorContext::Function {
-                    result = result.and(Indentation::new().generate(f, info.indent()));
-                }
+                result = result.and(Indentation::new().generate(f, &gnu_brace_info(info)));
             }
             IndentationStyle::KnR => {
                 result = result.and(write!(f, " "));
@@ -667,3 +1691,26 @@ where HT: CodeGenerate,{
         result
     }
 }
+
+impl<HT> CodeGenerate for HeaderPlusBody<HT>
+where HT: CodeGenerate + Clone,{
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        self.generate_with_header(f, info, |f, _| self.header.generate(f, info))
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::HeaderPlusBody { header: &self.header, body: &self.body }
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::HeaderPlusBody { header: &mut self.header, body: &mut self.body }
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.header, &self.body]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.header, &mut self.body]
+    }
+}
@@ -0,0 +1,353 @@
+use std::path::Path;
+use crate::config::ConfigError;
+use crate::setup::*;
+
+/// A style imported from a .clang-format file
+///
+/// Keys which have no equivalent in CodeGenerationInfo are listed in
+/// unsupported_keys, so they can be reported to the user.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClangFormatStyle {
+    pub info: CodeGenerationInfo,
+    pub unsupported_keys: Vec<String>,
+}
+
+/// A `Key: Value` entry of a .clang-format file
+///
+/// Nested mappings are flattened, so `BraceWrapping: { AfterFunction: true }`
+/// becomes the key `BraceWrapping.AfterFunction`.
+struct Entry {
+    line: usize,
+    key: String,
+    value: String,
+}
+
+impl Entry {
+    fn invalid(&self, expected: &str) -> ConfigError {
+        ConfigError::InvalidValue {
+            line: self.line,
+            key: self.key.clone(),
+            value: self.value.clone(),
+            expected: String::from(expected),
+        }
+    }
+
+    /// The top level key of this entry
+    fn parent(&self) -> &str {
+        self.key.split('.').next().unwrap_or(&self.key)
+    }
+
+    fn usize(&self) -> Result<usize, ConfigError> {
+        self.value.parse().map_err(|_| self.invalid("a non-negative integer"))
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value.strip_prefix('"').and_then(|value| value.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(" #") {
+        Some(index) => &line[..index],
+        None if line.trim_start().starts_with('#') => "",
+        None => line,
+    }
+}
+
+/// Parses the subset of YAML used by .clang-format files
+///
+/// Only the documents for C and C++ (or without a Language key) are kept.
+fn parse_entries(config: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut document = Vec::new();
+    let mut parent = String::new();
+
+    let mut finish_document = |document: &mut Vec<Entry>| {
+        let language = document.iter().find(|entry| entry.key == "Language");
+        if language.is_none_or(|entry| entry.value == "Cpp" || entry.value == "C") {
+            entries.append(document);
+        }
+        document.clear();
+    };
+
+    for (index, line) in config.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_comment(line).trim_end();
+        if line.trim() == "---" || line.trim() == "..." {
+            finish_document(&mut document);
+            continue;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let is_nested = line.starts_with(' ') || line.starts_with('\t');
+        let (key, value) = match line.trim().split_once(':') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => continue,
+        };
+
+        let key = if is_nested && !parent.is_empty() {
+            format!("{}.{}", parent, key)
+        } else {
+            parent = String::from(key);
+            String::from(key)
+        };
+
+        if let Some(mapping) = value.strip_prefix('{').and_then(|value| value.strip_suffix('}')) {
+            for item in mapping.split(',') {
+                if let Some((child, value)) = item.split_once(':') {
+                    document.push(Entry {
+                        line: line_number,
+                        key: format!("{}.{}", key, child.trim()),
+                        value: String::from(unquote(value)),
+                    });
+                }
+            }
+        } else if !value.is_empty() {
+            document.push(Entry { line: line_number, key, value: String::from(unquote(value)) });
+        }
+    }
+    finish_document(&mut document);
+
+    entries
+}
+
+impl ClangFormatStyle {
+    /// Imports the style settings of a .clang-format file
+    ///
+    /// The import starts from the LLVM style, like clang-format does, or from
+    /// the style named by BasedOnStyle. The supported keys are
+    /// BreakBeforeBraces, BraceWrapping (AfterFunction, AfterStruct,
+    /// AfterEnum and AfterControlStatement), IndentWidth, TabWidth, UseTab, ColumnLimit,
+    /// LineEnding, PointerAlignment, SpaceBeforeParens, SpaceBeforeParensOptions,
+    /// SpacesInParentheses and AlignConsecutiveMacros (which aligns defines).
+    ///
+    /// Like clang-format, BraceWrapping is only used when BreakBeforeBraces
+    /// is Custom, and SpaceBeforeParensOptions only when SpaceBeforeParens is
+    /// Custom. `BreakBeforeBraces: Whitesmiths` cannot be generated, so it is
+    /// listed in unsupported_keys and the braces of the base style are kept.
+    ///
+    /// ```
+    /// # use code_generator::ClangFormatStyle;
+    /// # use code_generator::IndentationStyle;
+    /// # use code_generator::IndentationType;
+    /// #
+    /// let style = ClangFormatStyle::parse("
+    /// ---
+    /// BasedOnStyle: LLVM
+    /// BreakBeforeBraces: Allman
+    /// IndentWidth: 4
+    /// UseTab: ForIndentation
+    /// TabWidth: 4
+    /// ColumnLimit: 100
+    /// SortIncludes: false
+    /// ...
+    /// ").unwrap();
+    /// assert_eq!(IndentationStyle::Allman, style.info.indent_style);
+    /// assert_eq!(4, style.info.indent_amount);
+    /// assert_eq!(IndentationType::SmartTabs, style.info.indent_type);
+    /// assert_eq!(4, style.info.tab_width);
+    /// assert_eq!(Some(100), style.info.max_line_width);
+    /// assert_eq!(vec![String::from("SortIncludes")], style.unsupported_keys);
+    /// ```
+    pub fn parse(config: &str) -> Result<ClangFormatStyle, ConfigError> {
+        let entries = parse_entries(config);
+
+        let mut info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_ident_amount(2)
+            .with_tab_width(8)
+            .with_max_line_width(80)
+            .with_spacing(Spacing::new().with_pointer_alignment(PointerAlignment::Right));
+        let left_pointers = info.spacing.with_pointer_alignment(PointerAlignment::Left);
+        if let Some(entry) = entries.iter().rev().find(|entry| entry.key == "BasedOnStyle") {
+            info = match entry.value.to_lowercase().as_str() {
+                "llvm" | "inheritparentconfig" => info,
+                "google" | "chromium" => CodeGenerationInfo { spacing: left_pointers, ..info },
+                "mozilla" => {
+                    let mut info = CodeGenerationInfo { spacing: left_pointers, ..info };
+                    info.set_brace_styles(Some(IndentationStyle::Allman), Some(IndentationStyle::Allman));
+                    info
+                }
+                "webkit" => {
+                    let mut info = CodeGenerationInfo {
+                        indent_amount: 4,
+                        max_line_width: None,
+                        spacing: left_pointers,
+                        ..info
+                    };
+                    info.set_brace_styles(Some(IndentationStyle::Allman), None);
+                    info
+                }
+                "microsoft" => CodeGenerationInfo {
+                    indent_amount: 4,
+                    indent_style: IndentationStyle::Allman,
+                    max_line_width: Some(120),
+                    ..info
+                },
+                "gnu" => CodeGenerationInfo {
+                    indent_style: IndentationStyle::GNU,
+                    max_line_width: Some(79),
+                    spacing: info.spacing.with_before_call_paren(true),
+                    ..info
+                },
+                _ => return Err(entry.invalid(
+                    "one of LLVM, Google, Chromium, Mozilla, WebKit, Microsoft, GNU or InheritParentConfig"
+                )),
+            };
+        }
+
+        // Applied last, so they refine the style set by BreakBeforeBraces
+        // and SpaceBeforeParens wherever those appear
+        let is_custom = |key: &str| entries.iter()
+            .rfind(|entry| entry.key == key)
+            .is_some_and(|entry| entry.value == "Custom");
+        let (options, entries): (Vec<&Entry>, Vec<&Entry>) = entries.iter()
+            .partition(|entry| entry.parent() == "BraceWrapping" || entry.parent() == "SpaceBeforeParensOptions");
+        let options = options.into_iter().filter(|entry| match entry.parent() {
+            "BraceWrapping" => is_custom("BreakBeforeBraces"),
+            _ => is_custom("SpaceBeforeParens"),
+        });
+
+        let mut unsupported_keys = Vec::new();
+        for entry in entries.into_iter().chain(options) {
+            if !info.apply_clang_format_entry(entry)? {
+                let key = entry.parent();
+                if !unsupported_keys.iter().any(|unsupported| unsupported == key) {
+                    unsupported_keys.push(String::from(key));
+                }
+            }
+        }
+
+        Ok(ClangFormatStyle { info, unsupported_keys })
+    }
+
+    /// Imports the style settings of a .clang-format file on disk
+    pub fn from_file(path: impl AsRef<Path>) -> Result<ClangFormatStyle, ConfigError> {
+        let config = std::fs::read_to_string(path)?;
+        ClangFormatStyle::parse(&config)
+    }
+}
+
+impl CodeGenerationInfo {
+    /// Applies a .clang-format entry, returning false if it is not supported
+    fn apply_clang_format_entry(&mut self, entry: &Entry) -> Result<bool, ConfigError> {
+        match entry.key.as_str() {
+            "BasedOnStyle" | "Language" => (),
+            // Custom keeps the braces of the base style, for BraceWrapping to refine
+            "BreakBeforeBraces" if entry.value == "Custom" => (),
+            // CodeBody cannot generate Whitesmiths braces
+            "BreakBeforeBraces" if entry.value == "Whitesmiths" => return Ok(false),
+            "BreakBeforeBraces" => {
+                let (indent_style, function_style, type_style) = match entry.value.as_str() {
+                    "Attach" => (IndentationStyle::KnR, None, None),
+                    "Linux" | "Stroustrup" | "WebKit" => {
+                        (IndentationStyle::KnR, Some(IndentationStyle::Allman), None)
+                    }
+                    "Mozilla" => (
+                        IndentationStyle::KnR,
+                        Some(IndentationStyle::Allman),
+                        Some(IndentationStyle::Allman)
+                    ),
+                    "Allman" => (IndentationStyle::Allman, None, None),
+                    "GNU" => (IndentationStyle::GNU, None, None),
+                    _ => return Err(entry.invalid(
+                        "one of Attach, Linux, Mozilla, Stroustrup, Allman, Whitesmiths, GNU, WebKit or Custom"
+                    )),
+                };
+                self.indent_style = indent_style;
+                self.set_brace_styles(function_style, type_style);
+            }
+            "BraceWrapping.AfterFunction" => self.set_brace_wrapping(entry, &[GeneratorContext::Function])?,
+            "BraceWrapping.AfterStruct" => self.set_brace_wrapping(entry, &[GeneratorContext::Struct])?,
+            "BraceWrapping.AfterEnum" => self.set_brace_wrapping(entry, &[GeneratorContext::Enum])?,
+            "BraceWrapping.AfterControlStatement" => self.set_brace_wrapping(
+                entry,
+                &[GeneratorContext::If, GeneratorContext::While, GeneratorContext::ForLoop]
+            )?,
+            "IndentWidth" => self.indent_amount = entry.usize()?,
+            "TabWidth" => self.tab_width = entry.usize()?,
+            "UseTab" => self.indent_type = match entry.value.as_str() {
+                "Never" | "false" => IndentationType::Spaces,
+                "ForIndentation" | "AlignWithSpaces" => IndentationType::SmartTabs,
+                "ForContinuationAndIndentation" | "Always" | "true" => IndentationType::Tabs,
+                _ => return Err(entry.invalid(
+                    "one of Never, ForIndentation, ForContinuationAndIndentation, AlignWithSpaces or Always"
+                )),
+            },
+            "ColumnLimit" => self.max_line_width = match entry.usize()? {
+                0 => None,
+                column_limit => Some(column_limit),
+            },
+            "LineEnding" => self.new_line_type = match entry.value.as_str() {
+                "LF" | "DeriveLF" => NewLineType::Nl,
+                "CRLF" | "DeriveCRLF" => NewLineType::CrNl,
+                _ => return Err(entry.invalid("one of LF, CRLF, DeriveLF or DeriveCRLF")),
+            },
+            "PointerAlignment" => self.spacing.pointer_alignment = match entry.value.as_str() {
+                "Left" => PointerAlignment::Left,
+                "Right" => PointerAlignment::Right,
+                "Middle" => PointerAlignment::Middle,
+                _ => return Err(entry.invalid("one of Left, Right or Middle")),
+            },
+            "SpaceBeforeParens" => {
+                let (after_control_keyword, before_call_paren) = match entry.value.as_str() {
+                    "Never" => (false, false),
+                    "ControlStatements" | "ControlStatementsExceptControlMacros" | "Custom" => (true, false),
+                    "NonEmptyParentheses" | "Always" => (true, true),
+                    _ => return Err(entry.invalid(
+                        "one of Never, ControlStatements, ControlStatementsExceptControlMacros, NonEmptyParentheses, Always or Custom"
+                    )),
+                };
+                self.spacing.after_control_keyword = after_control_keyword;
+                self.spacing.before_call_paren = before_call_paren;
+            }
+            "SpaceBeforeParensOptions.AfterControlStatements" => {
+                self.spacing.after_control_keyword = entry.value == "true";
+            }
+            "SpaceBeforeParensOptions.AfterFunctionDeclarationName" |
+            "SpaceBeforeParensOptions.AfterFunctionDefinitionName" => {
+                self.spacing.before_call_paren = entry.value == "true";
+            }
+            "SpacesInParentheses" => self.spacing.inside_parens = entry.value == "true",
+            "AlignConsecutiveMacros" | "AlignConsecutiveMacros.Enabled" => {
+                self.align_defines = match entry.value.as_str() {
+                    "None" | "false" => false,
+                    "Consecutive" | "AcrossEmptyLines" | "AcrossComments" |
+                    "AcrossEmptyLinesAndComments" | "true" => true,
+                    _ => return Err(entry.invalid(
+                        "true, false, None, Consecutive, AcrossEmptyLines, AcrossComments or AcrossEmptyLinesAndComments"
+                    )),
+                };
+            }
+            key if key.starts_with("AlignConsecutiveMacros.") => (),
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    /// Replaces the brace placement of functions and of struct and enum types
+    fn set_brace_styles(&mut self, function_style: Option<IndentationStyle>, type_style: Option<IndentationStyle>) {
+        self.context_styles = ContextStyles::new();
+        self.context_styles.set(GeneratorContext::Function, function_style);
+        self.context_styles.set(GeneratorContext::Struct, type_style);
+        self.context_styles.set(GeneratorContext::Enum, type_style);
+    }
+
+    /// Sets the brace placement of some contexts from a BraceWrapping entry
+    fn set_brace_wrapping(&mut self, entry: &Entry, contexts: &[GeneratorContext]) -> Result<(), ConfigError> {
+        let style = match entry.value.as_str() {
+            "true" | "Always" | "MultiLine" => IndentationStyle::Allman,
+            "false" | "Never" => IndentationStyle::KnR,
+            _ => return Err(entry.invalid("true, false, Never, MultiLine or Always")),
+        };
+        for context in contexts {
+            self.context_styles.set(*context, Some(style));
+        }
+        Ok(())
+    }
+}
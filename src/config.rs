@@ -0,0 +1,392 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::path::Path;
+use crate::identifier::{IdentifierPolicy, TargetLanguage};
+use crate::setup::*;
+
+/// An error found while loading a style from a config file
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Syntax { line: usize, message: String },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String, value: String, expected: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "could not read config: {}", error),
+            ConfigError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            ConfigError::UnknownKey { line, key } => write!(f, "line {}: unknown key '{}'", line, key),
+            ConfigError::InvalidValue { line, key, value, expected } => write!(
+                f,
+                "line {}: invalid value '{}' for '{}', expected {}",
+                line, value, key, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        ConfigError::Io(error)
+    }
+}
+
+/// A single `key = value` entry of a config file
+struct Entry {
+    line: usize,
+    key: String,
+    value: String,
+}
+
+impl Entry {
+    fn invalid(&self, expected: &str) -> ConfigError {
+        ConfigError::InvalidValue {
+            line: self.line,
+            key: self.key.clone(),
+            value: self.value.clone(),
+            expected: String::from(expected),
+        }
+    }
+
+    /// The value with surrounding quotes removed
+    fn text(&self) -> &str {
+        unquote(&self.value)
+    }
+
+    fn usize(&self) -> Result<usize, ConfigError> {
+        self.text().parse().map_err(|_| self.invalid("a non-negative integer"))
+    }
+
+    fn bool(&self) -> Result<bool, ConfigError> {
+        match self.text() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(self.invalid("true or false")),
+        }
+    }
+
+    fn choice<T: Clone>(&self, options: &[(&str, T)]) -> Result<T, ConfigError> {
+        self.find_choice(options).ok_or_else(|| self.invalid(&one_of(options)))
+    }
+
+    fn find_choice<T: Clone>(&self, options: &[(&str, T)]) -> Option<T> {
+        let value = normalize(self.text());
+        options.iter()
+            .find(|(option, _)| normalize(option) == value)
+            .map(|(_, choice)| choice.clone())
+    }
+
+    /// A case type name, or `custom:<first_chunk>:<other_chunks>:<separator>`
+    ///
+    /// The separator is the rest of the value, so it may contain colons.
+    fn case_type(&self) -> Result<CaseType, ConfigError> {
+        let case_types = [
+            ("flatcase", CaseType::FlatCase),
+            ("SCREAMINGCASE", CaseType::ScreamingCase),
+            ("camelCase", CaseType::CamelCase),
+            ("PascalCase", CaseType::PascalCase),
+            ("snake_case", CaseType::SnakeCase),
+            ("SCREAMING_SNAKE_CASE", CaseType::ScreamingSnakeCase),
+            ("kebab-case", CaseType::KebabCase),
+            ("SCREAMING-KEBAB-CASE", CaseType::ScreamingKebabCase),
+            ("Train-Case", CaseType::TrainCase),
+            ("Ada_Case", CaseType::AdaCase),
+            ("dot.case", CaseType::DotCase),
+        ];
+        let chunk_cases = [
+            ("lower", ChunkCase::Lower),
+            ("upper", ChunkCase::Upper),
+            ("capitalized", ChunkCase::Capitalized),
+        ];
+        let custom = |text: &str| {
+            let mut fields = text.strip_prefix("custom:")?.splitn(3, ':');
+            let mut chunk_case = || {
+                let field = normalize(fields.next()?);
+                chunk_cases.iter().find(|(name, _)| *name == field).map(|(_, case)| *case)
+            };
+            let first_chunk = chunk_case()?;
+            let other_chunks = chunk_case()?;
+            let separator = Cow::Owned(fields.next()?.to_string());
+            Some(CaseType::Custom { separator, first_chunk, other_chunks })
+        };
+
+        self.find_choice(&case_types)
+            .or_else(|| custom(self.text()))
+            .ok_or_else(|| self.invalid(&format!(
+                "{} or custom:<first_chunk>:<other_chunks>:<separator> with chunks {}",
+                one_of(&case_types),
+                chunk_cases.map(|(name, _)| name).join(", ")
+            )))
+    }
+
+    fn string_list(&self) -> Result<Vec<String>, ConfigError> {
+        let value = self.value.trim();
+        let inner = value.strip_prefix('[')
+            .and_then(|value| value.strip_suffix(']'))
+            .ok_or_else(|| self.invalid("a list of strings like [\"USB\", \"CRC\"]"))?;
+        Ok(split_outside_quotes(inner, ',')
+            .map(|item| unquote(item.trim()))
+            .filter(|item| !item.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+fn one_of<T>(options: &[(&str, T)]) -> String {
+    let names: Vec<&str> = options.iter().map(|(option, _)| *option).collect();
+    format!("one of {}", names.join(", "))
+}
+
+/// Compares option names ignoring case and word separators
+fn normalize(value: &str) -> String {
+    value.chars()
+        .filter(|char| !matches!(char, '_' | '-' | '.' | ' '))
+        .flat_map(|char| char.to_lowercase())
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    value.strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Splits a value at every `separator` which is not inside a quoted string
+fn split_outside_quotes(value: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut in_string = false;
+    value.split(move |char| {
+        if char == '"' {
+            in_string = !in_string;
+        }
+        char == separator && !in_string
+    })
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (index, char) in line.char_indices() {
+        match char {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => (),
+        }
+    }
+    line
+}
+
+fn parse_entries(config: &str) -> Result<Vec<Entry>, ConfigError> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+
+    for (index, line) in config.lines().enumerate() {
+        let line_number = index + 1;
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[') {
+            let name = name.strip_suffix(']').ok_or_else(|| ConfigError::Syntax {
+                line: line_number,
+                message: String::from("section header is missing ']'"),
+            })?;
+            section = format!("{}.", name.trim());
+            continue;
+        }
+
+        let (key, value) = line.split_once('=').ok_or_else(|| ConfigError::Syntax {
+            line: line_number,
+            message: format!("expected 'key = value', found '{}'", line),
+        })?;
+        entries.push(Entry {
+            line: line_number,
+            key: format!("{}{}", section, key.trim()),
+            value: value.trim().to_string(),
+        });
+    }
+
+    Ok(entries)
+}
+
+const INDENT_STYLES: &[(&str, IndentationStyle)] = &[
+    ("Allman", IndentationStyle::Allman),
+    ("GNU", IndentationStyle::GNU),
+    ("Whitesmiths", IndentationStyle::Whitesmiths),
+    ("KnR", IndentationStyle::KnR),
+    ("Ratliff", IndentationStyle::Ratliff),
+    ("Horstmann", IndentationStyle::Horstmann),
+    ("Pico", IndentationStyle::Pico),
+    ("Lisp", IndentationStyle::Lisp),
+    ("None", IndentationStyle::None),
+];
+
+impl CodeGenerationInfo {
+    /// Loads a style from the text of a config file
+    ///
+    /// The config is made of `key = value` lines. `[case_types]`,
+    /// `[spacing]` and `[context_styles]` sections (or prefixed keys like
+    /// `spacing.inside_parens`) set the case types, spacing options and the
+    /// per context indentation styles. Comments start with
+    /// `#`, and values may be quoted, so simple TOML files are also accepted.
+    /// The `style` key picks the CodeStyle the other keys are applied on top
+    /// of, no matter where it appears. Besides the case type names, case
+    /// types may be `custom:<first_chunk>:<other_chunks>:<separator>`, e.g.
+    /// `custom:upper:capitalized:::` for "UART::Init".
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::ConfigError;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// let info = CodeGenerationInfo::from_config_str("
+    ///     style = \"KnR\"
+    ///     indent_type = tabs
+    ///     new_line_type = nl
+    ///
+    ///     [case_types]
+    ///     function_name = camelCase
+    ///     type_name = \"custom:upper:capitalized:::\"
+    ///     acronyms = [\"USB\"]
+    /// ").unwrap();
+    /// let name = Name::new_with_type("UsbInit", NameType::Function);
+    /// assert_eq!("usbInit", format!("{}", name.display(&info)));
+    /// let name = Name::new_with_type("uartConfig", NameType::Type);
+    /// assert_eq!("UART::Config", format!("{}", name.display(&info)));
+    ///
+    /// let error = CodeGenerationInfo::from_config_str("indent_amont = 4").unwrap_err();
+    /// assert_eq!("line 1: unknown key 'indent_amont'", error.to_string());
+    /// ```
+    pub fn from_config_str(config: &str) -> Result<CodeGenerationInfo, ConfigError> {
+        let entries = parse_entries(config)?;
+
+        let mut info = CodeGenerationInfo::new();
+        if let Some(entry) = entries.iter().rev().find(|entry| entry.key == "style") {
+            info = CodeGenerationInfo::from_style(entry.choice(&[
+                ("Allman", CodeStyle::Allman),
+                ("GNU", CodeStyle::GNU),
+                ("Whitesmiths", CodeStyle::Whitesmiths),
+                ("KnR", CodeStyle::KnR),
+                ("Ratliff", CodeStyle::Ratliff),
+                ("Horstmann", CodeStyle::Horstmann),
+                ("Pico", CodeStyle::Pico),
+                ("Lisp", CodeStyle::Lisp),
+                ("Minimal", CodeStyle::Minimal),
+                ("Linux", CodeStyle::Linux),
+                ("Default", CodeStyle::Default),
+            ])?);
+        }
+
+        for entry in entries.iter() {
+            info.apply_config_entry(entry)?;
+        }
+
+        Ok(info)
+    }
+
+    /// Loads a style from a config file, see from_config_str for the format
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<CodeGenerationInfo, ConfigError> {
+        let config = std::fs::read_to_string(path)?;
+        CodeGenerationInfo::from_config_str(&config)
+    }
+
+    fn apply_config_entry(&mut self, entry: &Entry) -> Result<(), ConfigError> {
+        let case_types = &mut self.case_types;
+        match entry.key.as_str() {
+            "style" => (),
+            "indent_style" => self.indent_style = entry.choice(INDENT_STYLES)?,
+            "indent_type" => self.indent_type = entry.choice(&[
+                ("spaces", IndentationType::Spaces),
+                ("tabs", IndentationType::Tabs),
+                ("smart_tabs", IndentationType::SmartTabs),
+            ])?,
+            "indent_amount" => self.indent_amount = entry.usize()?,
+            "tab_width" => self.tab_width = entry.usize()?,
+            "new_line_type" => self.new_line_type = entry.choice(&[
+                ("cr", NewLineType::Cr),
+                ("nl", NewLineType::Nl),
+                ("crnl", NewLineType::CrNl),
+                ("none", NewLineType::None),
+            ])?,
+            "max_line_width" => self.max_line_width = match entry.text() {
+                "none" => None,
+                _ => Some(entry.usize()?),
+            },
+            "list_wrap_style" => self.list_wrap_style = entry.choice(&[
+                ("one_per_line", ListWrapStyle::OnePerLine),
+                ("align_to_paren", ListWrapStyle::AlignToParen),
+                ("hanging_indent", ListWrapStyle::HangingIndent),
+            ])?,
+            "align_columns" => {
+                self.align_columns = entry.bool()?;
+                self.align_defines = self.align_columns;
+            }
+            "align_defines" => self.align_defines = entry.bool()?,
+            "spacing.after_control_keyword" => self.spacing.after_control_keyword = entry.bool()?,
+            "spacing.before_call_paren" => self.spacing.before_call_paren = entry.bool()?,
+            "spacing.inside_parens" => self.spacing.inside_parens = entry.bool()?,
+            "spacing.around_binary_operators" => self.spacing.around_binary_operators = entry.bool()?,
+            "spacing.pointer_alignment" => self.spacing.pointer_alignment = entry.choice(&[
+                ("left", PointerAlignment::Left),
+                ("right", PointerAlignment::Right),
+                ("middle", PointerAlignment::Middle),
+            ])?,
+            key if key.starts_with("context_styles.") => {
+                let context = match &key["context_styles.".len()..] {
+                    "if" => GeneratorContext::If,
+                    "while" => GeneratorContext::While,
+                    "for_loop" => GeneratorContext::ForLoop,
+                    "function" => GeneratorContext::Function,
+                    "file" => GeneratorContext::File,
+                    "struct" => GeneratorContext::Struct,
+                    "enum" => GeneratorContext::Enum,
+                    "other" => GeneratorContext::Other,
+                    _ => return Err(ConfigError::UnknownKey { line: entry.line, key: entry.key.clone() }),
+                };
+                self.context_styles.set(context, Some(entry.choice(INDENT_STYLES)?));
+            }
+            "case_types.const_define" => case_types.const_define_case = entry.case_type()?,
+            "case_types.function_name" => case_types.function_name_case = entry.case_type()?,
+            "case_types.member_name" => case_types.member_name_case = entry.case_type()?,
+            "case_types.type_name" => case_types.type_name_case = entry.case_type()?,
+            "case_types.file_name" => case_types.file_name_case = entry.case_type()?,
+            "case_types.default" => case_types.default_case = entry.case_type()?,
+            "case_types.digit_boundary" => case_types.digit_boundary = entry.choice(&[
+                ("attached", DigitBoundary::Attached),
+                ("before", DigitBoundary::Before),
+                ("around", DigitBoundary::Around),
+            ])?,
+            "case_types.acronyms" => case_types.acronyms = entry.string_list()?,
+            "case_types.target_language" => case_types.target_language = entry.choice(&[
+                ("C", TargetLanguage::C),
+                ("Cpp", TargetLanguage::Cpp),
+            ])?,
+            "case_types.identifier_policy" => {
+                let expected = "allow, error, suffix:<text> or prefix:<text>";
+                case_types.identifier_policy = match entry.text().split_once(':') {
+                    None if entry.text() == "allow" => IdentifierPolicy::Allow,
+                    None if entry.text() == "error" => IdentifierPolicy::Error,
+                    Some(("suffix", suffix)) => IdentifierPolicy::Suffix(Cow::Owned(suffix.to_string())),
+                    Some(("prefix", prefix)) => IdentifierPolicy::Prefix(Cow::Owned(prefix.to_string())),
+                    _ => return Err(entry.invalid(expected)),
+                };
+            }
+            _ => return Err(ConfigError::UnknownKey { line: entry.line, key: entry.key.clone() }),
+        }
+
+        Ok(())
+    }
+}
@@ -1,26 +1,258 @@
 use std::fmt;
 use crate::building_block_generators::*;
 use crate::setup::*;
+use crate::symbol_table::{declare_symbol, in_symbol_scope, resolve_symbol, SymbolScope};
+use crate::node::{Node, NodeMut};
+
+/// Joins a type and a name, placing the `*` of pointer types based on the
+/// pointer alignment
+fn declaration(type_name: &str, name: &str, pointer_alignment: PointerAlignment) -> String {
+    let base = type_name.trim_end_matches(['*', ' ']);
+    let stars = type_name[base.len()..].replace(' ', "");
+    if stars.is_empty() {
+        return format!("{} {}", type_name, name);
+    }
+
+    match pointer_alignment {
+        PointerAlignment::Left => format!("{}{} {}", base, stars, name),
+        PointerAlignment::Right => format!("{} {}{}", base, stars, name),
+        PointerAlignment::Middle => format!("{} {} {}", base, stars, name),
+    }
+}
+
+/// Generates a comment on the lines before a declaration
+///
+/// A single line is written as `/* text */`, and multiple lines as a block
+/// with a `*` at the start of every line. A `*/` in the text is written as
+/// `* /`, so it cannot end the comment early.
+fn generate_comment(f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo, comment: &Option<String>) -> fmt::Result {
+    let comment = match comment {
+        Some(comment) => comment.replace("*/", "* /"),
+        None => return fmt::Result::Ok(()),
+    };
+    let mut result = fmt::Result::Ok(());
+    let lines: Vec<&str> = comment.lines().collect();
+    if lines.len() == 1 {
+        result = result.and(write!(f, "/* {} */", lines[0]));
+    } else {
+        result = result.and(write!(f, "/*"));
+        for line in lines {
+            result = result.and(NewLine::new().generate(f, info));
+            result = result.and(Indentation::new().generate(f, info));
+            if line.is_empty() {
+                result = result.and(write!(f, " *"));
+            } else {
+                result = result.and(write!(f, " * {}", line));
+            }
+        }
+        result = result.and(NewLine::new().generate(f, info));
+        result = result.and(Indentation::new().generate(f, info));
+        result = result.and(write!(f, " */"));
+    }
+    result = result.and(NewLine::new().generate(f, info));
+    result.and(Indentation::new().generate(f, info))
+}
+
+/// Joins a keyword with the attributes which follow it
+fn with_attributes(keyword: &str, attributes: &[String]) -> String {
+    let mut header = String::from(keyword);
+    for attribute in attributes {
+        header.push(' ');
+        header.push_str(attribute);
+    }
+    header
+}
+
+/// The storage class of a function, written before its return type
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StorageClass {
+    Static,
+    Extern,
+    Inline,
+    StaticInline,
+}
+
+impl StorageClass {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            StorageClass::Static => "static",
+            StorageClass::Extern => "extern",
+            StorageClass::Inline => "inline",
+            StorageClass::StaticInline => "static inline",
+        }
+    }
+}
+
+/// A typed parameter like "Config* config"
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Parameter {
+    type_name: Name,
+    name: Name,
+}
+
+impl Parameter {
+    /// Creates a Parameter generator
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::Parameter;
+    /// # use code_generator::PointerAlignment;
+    /// # use code_generator::Spacing;
+    /// #
+    /// let parameter = Parameter::new(
+    ///     Name::new_with_type("char**", NameType::Bypass),
+    ///     Name::new("argValues")
+    /// );
+    /// let info = CodeGenerationInfo::new();
+    /// assert_eq!("char** arg_values", format!("{}", parameter.display(&info)));
+    ///
+    /// let right = Spacing::new().with_pointer_alignment(PointerAlignment::Right);
+    /// assert_eq!("char **arg_values", format!("{}", parameter.display(&info.clone().with_spacing(right))));
+    ///
+    /// let middle = Spacing::new().with_pointer_alignment(PointerAlignment::Middle);
+    /// assert_eq!("char ** arg_values", format!("{}", parameter.display(&info.clone().with_spacing(middle))));
+    /// ```
+    pub fn new(type_name: Name, name: Name) -> Parameter {
+        Parameter {
+            type_name: type_name.with_type(NameType::Type),
+            name: name.with_type(NameType::Member),
+        }
+    }
+
+    pub fn type_name(&self) -> &Name {
+        &self.type_name
+    }
+
+    pub fn type_name_mut(&mut self) -> &mut Name {
+        &mut self.type_name
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.name
+    }
+}
+
+impl CodeGenerate for Parameter {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let type_name = render(&self.type_name, info)?;
+        let name = render(&self.name, info)?;
+        write!(f, "{}", declaration(&type_name, &name, info.spacing.pointer_alignment))
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::Parameter(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Parameter(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.type_name, &self.name]
+    }
 
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.type_name, &mut self.name]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionSignature {
     function_name: Name,
-    parameters: SeparatedCode,
+    parameters: ParameterList,
     return_type: Name,
+    storage_class: Option<StorageClass>,
+    attributes: Vec<String>,
 }
 
 impl FunctionSignature {
     pub fn new(return_type: Name, name: Name, parameters: Vec<(Name, Name)>) -> FunctionSignature {
         let mut set = Vec::<Box<dyn CodeGenerate>>::new();
         for (type_name, param_name) in parameters {
-            set.push(Box::new(JoinedCode::new(vec![Box::new(type_name.with_type(NameType::Type)), Box::new(String::from(" ")), Box::new(param_name.with_type(NameType::Member))])))
+            set.push(Box::new(Parameter::new(type_name, param_name)))
         }
 
         FunctionSignature {
             return_type: return_type.with_type(NameType::Type),
             function_name: name.with_type(NameType::Function),
-            parameters: SeparatedCode::new(set, Box::new(String::from(", "))),
+            parameters: ParameterList::new(set),
+            storage_class: None,
+            attributes: Vec::new(),
         }
     }
+
+    /// Sets the storage class written before the return type
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::FunctionSignature;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::StorageClass;
+    /// #
+    /// let signature = FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("resetHandler"), vec![])
+    ///     .with_storage_class(StorageClass::Static)
+    ///     .with_attribute("__attribute__((weak))");
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+    /// assert_eq!(
+    ///     "static __attribute__((weak)) void reset_handler()",
+    ///     format!("{}", signature.display(&info))
+    /// );
+    /// ```
+    pub fn with_storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.storage_class = Some(storage_class);
+        self
+    }
+
+    /// Adds an attribute, written after the storage class
+    pub fn with_attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    pub fn storage_class(&self) -> Option<StorageClass> {
+        self.storage_class
+    }
+
+    pub fn attributes(&self) -> &[String] {
+        &self.attributes
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.function_name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.function_name
+    }
+
+    pub fn return_type(&self) -> &Name {
+        &self.return_type
+    }
+
+    pub fn return_type_mut(&mut self) -> &mut Name {
+        &mut self.return_type
+    }
+
+    pub fn parameters(&self) -> &ParameterList {
+        &self.parameters
+    }
+
+    pub fn parameters_mut(&mut self) -> &mut ParameterList {
+        &mut self.parameters
+    }
 }
 
 impl From<FunctionSignature> for FunctionDeclaration {
@@ -29,20 +261,55 @@ impl From<FunctionSignature> for FunctionDeclaration {
     }
 }
 
-impl CodeGenerate for FunctionSignature {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+impl FunctionSignature {
+    /// Generates the signature, fitting `suffix_width` characters written
+    /// after it on its last line, like the `;` of a declaration
+    pub(crate) fn generate_with_suffix(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo, suffix_width: usize) -> fmt::Result {
+        declare_symbol(&self.function_name, &info.case_types);
+        let return_type = render(&self.return_type, info)?;
+        let function_name = render(&self.function_name, info)?;
+        let mut header = String::new();
+        let storage_class = self.storage_class.map(|storage_class| storage_class.keyword());
+        for part in storage_class.into_iter().chain(self.attributes.iter().map(String::as_str)) {
+            header.push_str(part);
+            header.push(' ');
+        }
+        header.push_str(&declaration(&return_type, &function_name, info.spacing.pointer_alignment));
+        if info.spacing.before_call_paren {
+            header.push(' ');
+        }
         let mut result: fmt::Result = fmt::Result::Ok(());
-        result = result.and(self.return_type.generate(f, info));
-        result = result.and(write!(f, " "));
-        result = result.and(self.function_name.generate(f, info));
-        result = result.and(write!(f, "("));
-        result = result.and(self.parameters.generate(f, info));
-        result = result.and(write!(f, ")"));
+        result = result.and(write!(f, "{}", header));
+        result = result.and(self.parameters.generate_at_column(f, info, header.chars().count(), suffix_width));
 
         result
     }
 }
 
+impl CodeGenerate for FunctionSignature {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        self.generate_with_suffix(f, info, 0)
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::FunctionSignature(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::FunctionSignature(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.return_type, &self.function_name, &self.parameters]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.return_type, &mut self.function_name, &mut self.parameters]
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionDeclaration {
     signature: FunctionSignature
 }
@@ -53,40 +320,141 @@ impl FunctionDeclaration {
             signature: FunctionSignature::new(return_type, name, parameters)
         }
     }
+
+    pub fn signature(&self) -> &FunctionSignature {
+        &self.signature
+    }
+
+    pub fn signature_mut(&mut self) -> &mut FunctionSignature {
+        &mut self.signature
+    }
 }
 
 impl CodeGenerate for FunctionDeclaration {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let mut result: fmt::Result = fmt::Result::Ok(());
-        result = result.and(self.signature.generate(f, info));
+        result = result.and(self.signature.generate_with_suffix(f, info, ";".len()));
         result = result.and(write!(f, ";"));
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::FunctionDeclaration(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::FunctionDeclaration(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.signature]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.signature]
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Function {
     content: HeaderPlusBody<FunctionSignature>,
+    comment: Option<String>,
 }
 
 impl Function {
     pub fn new(signature: FunctionSignature, body: CodeSet) -> Function {
         Function {
-            content: HeaderPlusBody::new(signature, CodeBody::from_set(body))
+            content: HeaderPlusBody::new(signature, CodeBody::from_set(body)),
+            comment: None,
         }
     }
+
+    /// Sets a comment written on the lines before the function
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeSet;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::Function;
+    /// # use code_generator::FunctionSignature;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::NewLineType;
+    /// #
+    /// let function = Function::new(
+    ///     FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("uartInit"), vec![]),
+    ///     CodeSet::new(vec![Box::new("clockEnable();")])
+    /// ).with_comment("Initializes the UART\nCalled once at startup");
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+    /// assert_eq!(
+    /// "/*
+    ///  * Initializes the UART
+    ///  * Called once at startup
+    ///  */
+    /// void uart_init() {
+    ///     clockEnable();
+    /// }", format!("{}", function.display(&info)));
+    /// ```
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    pub fn signature(&self) -> &FunctionSignature {
+        self.content.header()
+    }
+
+    pub fn signature_mut(&mut self) -> &mut FunctionSignature {
+        self.content.header_mut()
+    }
+
+    pub fn body(&self) -> &CodeBody {
+        self.content.body()
+    }
+
+    pub fn body_mut(&mut self) -> &mut CodeBody {
+        self.content.body_mut()
+    }
 }
 
 impl CodeGenerate for Function {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::Function;
-        self.content.generate(f, info)
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let result = generate_comment(f, info, &self.comment);
+        let info = &info.with_context(GeneratorContext::Function);
+        result.and(self.content.generate_with_header(f, info, |f, brace_width| {
+            self.content.header().generate_with_suffix(f, info, brace_width)
+        }))
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::Function(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Function(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        self.content.children()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        self.content.children_mut()
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FunctionCall {
     name: Name,
-    params: SeparatedCode,
+    params: ParameterList,
     is_terminated: bool,
 }
 
@@ -94,7 +462,7 @@ impl FunctionCall {
     pub fn new(name: Name, params: Vec<Box<dyn CodeGenerate>>) -> FunctionCall {
         FunctionCall {
             name: name.with_type(NameType::Function),
-            params: SeparatedCode::new(params, Box::new(String::from(", "))),
+            params: ParameterList::new(params),
             is_terminated: false
         }
     }
@@ -102,19 +470,67 @@ impl FunctionCall {
     pub fn new_with_end(name: Name, params: Vec<Box<dyn CodeGenerate>>) -> FunctionCall {
         FunctionCall {
             name: name.with_type(NameType::Function),
-            params: SeparatedCode::new(params, Box::new(String::from(", "))),
+            params: ParameterList::new(params),
             is_terminated: true
         }
     }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.name
+    }
+
+    pub fn arguments(&self) -> &ParameterList {
+        &self.params
+    }
+
+    pub fn arguments_mut(&mut self) -> &mut ParameterList {
+        &mut self.params
+    }
+
+    /// Checks if the call is terminated by a ';'
+    pub fn is_terminated(&self) -> bool {
+        self.is_terminated
+    }
 }
 
 impl CodeGenerate for FunctionCall {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         let termination = if self.is_terminated {";"} else {""};
-        write!(f, "{}({}){}", self.name.display(info), self.params.display(info), termination)
+        let mut name = render(&self.name, info)?;
+        if info.spacing.before_call_paren {
+            name.push(' ');
+        }
+        let mut result: fmt::Result = fmt::Result::Ok(());
+        result = result.and(write!(f, "{}", name));
+        result = result.and(self.params.generate_at_column(f, info, name.chars().count(), termination.len()));
+        result = result.and(write!(f, "{}", termination));
+
+        result
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::FunctionCall(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::FunctionCall(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.name, &self.params]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.name, &mut self.params]
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeaderFile {
     file_name: Name,
     content: CodeSet,
@@ -124,20 +540,37 @@ impl HeaderFile {
     pub fn new(file_name: Name, content: CodeSet) -> HeaderFile {
         HeaderFile {
             file_name: file_name.with_type(NameType::FixedCase(CaseType::ScreamingSnakeCase)),
-            content: content,
+            content,
         }
     }
+
+    pub fn file_name(&self) -> &Name {
+        &self.file_name
+    }
+
+    pub fn file_name_mut(&mut self) -> &mut Name {
+        &mut self.file_name
+    }
+
+    pub fn content(&self) -> &CodeSet {
+        &self.content
+    }
+
+    pub fn content_mut(&mut self) -> &mut CodeSet {
+        &mut self.content
+    }
 }
 
 impl CodeGenerate for HeaderFile {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::File;
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let info = &info.with_context(GeneratorContext::File);
 
+        let file_name = render(&self.file_name, info)?;
         let mut result = fmt::Result::Ok(());
 
-        result = result.and(write!(f, "#ifndef {}_H", self.file_name.display(info)));
+        result = result.and(write!(f, "#ifndef {}_H", file_name));
         result = result.and(NewLine::new().generate(f, info));
-        result = result.and(write!(f, "#define {}_H", self.file_name.display(info)));
+        result = result.and(write!(f, "#define {}_H", file_name));
         result = result.and(NewLine::new().generate(f, info));
         result = result.and(NewLine::new().generate(f, info));
 
@@ -150,87 +583,293 @@ impl CodeGenerate for HeaderFile {
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::HeaderFile(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::HeaderFile(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.file_name, &self.content]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.file_name, &mut self.content]
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Enum {
-    content: HeaderPlusBody<String>,
     name: Name,
+    values: Vec<(Name, Option<i64>)>,
+    attributes: Vec<String>,
+    comment: Option<String>,
 }
 
 impl Enum {
     pub fn new(name: Name, values: Vec<(Name, Option<i64>)>) -> Enum {
+        Enum {
+            name: name.with_type(NameType::Type),
+            values: values.into_iter()
+                .map(|(member_name, value)| (member_name.with_type(NameType::Type), value))
+                .collect(),
+            attributes: Vec::new(),
+            comment: None,
+        }
+    }
+
+    /// Adds an attribute, written after the `enum` keyword
+    pub fn with_attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    /// Sets a comment written on the lines before the enum
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn attributes(&self) -> &[String] {
+        &self.attributes
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    fn content(&self, info: &CodeGenerationInfo) -> HeaderPlusBody<String> {
+        let column = self.values.iter()
+            .filter(|(_, value)| value.is_some())
+            .map(|(member_name, _)| resolve_symbol(member_name, &info.case_types).chars().count())
+            .max()
+            .map(|max_width| info.alignment_column(max_width));
+
         let mut code_values: Vec<Box<dyn CodeGenerate>> = Vec::new();
-        for (member_name, value) in values {
+        for (member_name, value) in self.values.iter() {
             if let Some(value) = value {
+                let padding = match column {
+                    Some(column) if info.align_columns => {
+                        let width = resolve_symbol(member_name, &info.case_types).chars().count();
+                        info.alignment_padding(width, column)
+                    }
+                    _ => String::from(" "),
+                };
                 code_values.push(Box::new(JoinedCode::new(
-                    vec![Box::new(member_name.with_type(NameType::Type)), Box::new(format!(" = {},", value))]
+                    vec![Box::new(member_name.clone()), Box::new(format!("{}= {},", padding, value))]
                 )));
             } else {
                 code_values.push(Box::new(JoinedCode::new(
-                    vec![Box::new(member_name.with_type(NameType::Type)), Box::new(String::from(","))]
+                    vec![Box::new(member_name.clone()), Box::new(String::from(","))]
                 )));
             }
         }
-        Enum {
-            name: name.with_type(NameType::Type),
-            content: HeaderPlusBody::new(
-                String::from("typedef enum"),
-                CodeBody::new(code_values)
-            )
-        }
+        HeaderPlusBody::new(
+            with_attributes("typedef enum", &self.attributes),
+            CodeBody::new(code_values)
+        )
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.name
+    }
+
+    /// The names of the values and their explicit values
+    pub fn values(&self) -> &[(Name, Option<i64>)] {
+        &self.values
+    }
+
+    pub fn values_mut(&mut self) -> &mut Vec<(Name, Option<i64>)> {
+        &mut self.values
     }
 }
 
 impl CodeGenerate for Enum {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::Enum;
-        let mut result = self.content.generate(f, info);
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        // enum values are declared in the enclosing scope, like the enum
+        declare_symbol(&self.name, &info.case_types);
+        for (member_name, _) in self.values.iter() {
+            declare_symbol(member_name, &info.case_types);
+        }
+
+        let mut result = generate_comment(f, info, &self.comment);
+        let info = &info.with_context(GeneratorContext::Enum);
+        result = result.and(self.content(info).generate(f, info));
         result = result.and(String::from(" ").generate(f, info));
         result = result.and(self.name.generate(f, info));
         result = result.and(String::from(";").generate(f, info));
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::Enum(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Enum(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        let mut children: Vec<&dyn CodeGenerate> = vec![&self.name];
+        children.extend(self.values.iter().map(|(name, _)| name as &dyn CodeGenerate));
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        let mut children: Vec<&mut dyn CodeGenerate> = vec![&mut self.name];
+        children.extend(self.values.iter_mut().map(|(name, _)| name as &mut dyn CodeGenerate));
+        children
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Struct {
-    content: HeaderPlusBody<String>,
     name: Name,
+    members: Vec<(Name, Name)>,
+    attributes: Vec<String>,
+    comment: Option<String>,
 }
 
 impl Struct {
     pub fn new(name: Name, values: Vec<(Name, Name)>) -> Struct {
+        Struct {
+            name: name.with_type(NameType::Type),
+            members: values.into_iter()
+                .map(|(member_type, member_name)| (
+                    member_type.with_type(NameType::Type),
+                    member_name.with_type(NameType::Member)
+                ))
+                .collect(),
+            attributes: Vec::new(),
+            comment: None,
+        }
+    }
+
+    /// Adds an attribute, written after the `struct` keyword
+    pub fn with_attribute(mut self, attribute: impl Into<String>) -> Self {
+        self.attributes.push(attribute.into());
+        self
+    }
+
+    /// Sets a comment written on the lines before the struct
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn attributes(&self) -> &[String] {
+        &self.attributes
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.comment.as_deref()
+    }
+
+    fn content(&self, info: &CodeGenerationInfo) -> HeaderPlusBody<String> {
+        let column = self.members.iter()
+            .map(|(member_type, _)| resolve_symbol(member_type, &info.case_types).chars().count())
+            .max()
+            .map(|max_width| info.alignment_column(max_width));
+
         let mut code_values: Vec<Box<dyn CodeGenerate>> = Vec::new();
-        for (member_type, member_name) in values {
+        for (member_type, member_name) in self.members.iter() {
+            let padding = match column {
+                Some(column) if info.align_columns => {
+                    let width = resolve_symbol(member_type, &info.case_types).chars().count();
+                    info.alignment_padding(width, column)
+                }
+                _ => String::from(" "),
+            };
             code_values.push(Box::new(JoinedCode::new(vec![
-                Box::new(member_type.with_type(NameType::Type)),
-                Box::new(String::from(" ")),
-                Box::new(member_name.with_type(NameType::Member)),
+                Box::new(member_type.clone()),
+                Box::new(padding),
+                Box::new(member_name.clone()),
                 Box::new(String::from(";")),
             ])));
         }
-        Struct {
-            name: name.with_type(NameType::Type),
-            content: HeaderPlusBody::new(
-                String::from("typedef struct"),
-                CodeBody::new(code_values)
-            )
-        }
+        HeaderPlusBody::new(
+            with_attributes("typedef struct", &self.attributes),
+            CodeBody::new(code_values)
+        )
+    }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.name
+    }
+
+    /// The types and names of the members
+    pub fn members(&self) -> &[(Name, Name)] {
+        &self.members
+    }
+
+    pub fn members_mut(&mut self) -> &mut Vec<(Name, Name)> {
+        &mut self.members
     }
 }
 
 impl CodeGenerate for Struct {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::Struct;
-        let mut result = self.content.generate(f, info);
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        declare_symbol(&self.name, &info.case_types);
+
+        let mut result = generate_comment(f, info, &self.comment);
+        let info = &info.with_context(GeneratorContext::Struct);
+        let scope = SymbolScope::Struct(resolve_symbol(&self.name, &info.case_types));
+        result = result.and(in_symbol_scope(scope, || {
+            for (_, member_name) in self.members.iter() {
+                declare_symbol(member_name, &info.case_types);
+            }
+            self.content(info).generate(f, info)
+        }));
         result = result.and(String::from(" ").generate(f, info));
         result = result.and(self.name.generate(f, info));
         result = result.and(String::from(";").generate(f, info));
 
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::Struct(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Struct(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        let mut children: Vec<&dyn CodeGenerate> = vec![&self.name];
+        for (member_type, member_name) in self.members.iter() {
+            children.push(member_type);
+            children.push(member_name);
+        }
+        children
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        let mut children: Vec<&mut dyn CodeGenerate> = vec![&mut self.name];
+        for (member_type, member_name) in self.members.iter_mut() {
+            children.push(member_type);
+            children.push(member_name);
+        }
+        children
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TypeDef {
     defined_type: String,
     name: Name,
@@ -239,26 +878,57 @@ pub struct TypeDef {
 impl TypeDef {
     pub fn new(name: Name, defined_type: String) -> TypeDef {
         TypeDef { 
-            defined_type: defined_type,
+            defined_type,
             name: name.with_type(NameType::Type)
         }
     }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.name
+    }
+
+    pub fn defined_type(&self) -> &str {
+        &self.defined_type
+    }
 }
 
 impl CodeGenerate for TypeDef {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        declare_symbol(&self.name, &info.case_types);
+
         let mut result = String::from("typedef ").generate(f, info);
         result = result.and(self.defined_type.generate(f, info));
         result = result.and(String::from(" ").generate(f, info));
         result = result.and(self.name.generate(f, info));
         result = result.and(String::from(";").generate(f, info));
 
-
         result
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::TypeDef(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::TypeDef(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.defined_type, &self.name]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.defined_type, &mut self.name]
+    }
 }
 
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConstDefine<VT> {
     name: Name,
     value: VT,
@@ -276,21 +946,69 @@ impl<VT> ConstDefine<VT> {
     /// #
     /// let define = ConstDefine::new(Name::new("name"), String::from("2"));
     /// let mut info = CodeGenerationInfo::new();
-    /// assert_eq!("#define NAME 2", format!("{}", define.display(info)));
+    /// assert_eq!("#define NAME 2", format!("{}", define.display(&info)));
     /// ```
     pub fn new(name: Name, value: VT) -> ConstDefine<VT> {
         ConstDefine { name: name.with_type(NameType::ConstDefine), value }
     }
+
+    pub fn name(&self) -> &Name {
+        &self.name
+    }
+
+    pub fn name_mut(&mut self) -> &mut Name {
+        &mut self.name
+    }
+
+    pub fn value(&self) -> &VT {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut VT {
+        &mut self.value
+    }
 }
 
 impl<VT> CodeGenerate for ConstDefine<VT>
-where VT: CodeGenerate + 'static {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+where VT: CodeGenerate + Clone + 'static {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        declare_symbol(&self.name, &info.case_types);
+        let padding = match info.align_column {
+            Some(column) => {
+                let width = "#define ".len() + resolve_symbol(&self.name, &info.case_types).chars().count();
+                info.alignment_padding(width, column)
+            }
+            None => String::from(" "),
+        };
+        let info = &CodeGenerationInfo { align_column: None, ..info.clone() };
+
         let mut result = String::from("#define ").generate(f, info);
         result = result.and(self.name.generate(f, info));
-        result = result.and(String::from(" ").generate(f, info));
+        result = result.and(padding.generate(f, info));
         result = result.and(self.value.generate(f, info));
 
         result
     }
+
+    fn alignment_width(&self, info: &CodeGenerationInfo) -> Option<usize> {
+        // measured before the define is generated, so it is declared here
+        declare_symbol(&self.name, &info.case_types);
+        Some("#define ".len() + resolve_symbol(&self.name, &info.case_types).chars().count())
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::ConstDefine { name: &self.name, value: &self.value }
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::ConstDefine { name: &mut self.name, value: &mut self.value }
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        vec![&self.name, &self.value]
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        vec![&mut self.name, &mut self.value]
+    }
 }
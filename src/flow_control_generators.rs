@@ -1,9 +1,95 @@
+use std::borrow::Cow;
 use std::fmt;
 use crate::building_block_generators::*;
 use crate::setup::*;
+use crate::node::{Node, NodeMut};
 
+/// The header of a control statement like "if (condition)"
+///
+/// The clauses are separated by "; ", and the spacing around the
+/// parentheses follows the spacing options of the generation info.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ControlHeader {
+    keyword: Cow<'static, str>,
+    clauses: Vec<Box<dyn CodeGenerate>>,
+}
+
+impl ControlHeader {
+    /// Creates a ControlHeader generator
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::ControlHeader;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::Spacing;
+    /// #
+    /// let header = ControlHeader::new("while", vec![Box::new(String::from("running"))]);
+    /// let info = CodeGenerationInfo::new();
+    /// assert_eq!("while (running)", format!("{}", header.display(&info)));
+    ///
+    /// let info = info.with_spacing(
+    ///     Spacing::new().with_after_control_keyword(false).with_inside_parens(true)
+    /// );
+    /// assert_eq!("while( running )", format!("{}", header.display(&info)));
+    /// ```
+    pub fn new(keyword: impl Into<Cow<'static, str>>, clauses: Vec<Box<dyn CodeGenerate>>) -> ControlHeader {
+        ControlHeader { keyword: keyword.into(), clauses }
+    }
+
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn clauses(&self) -> &[Box<dyn CodeGenerate>] {
+        &self.clauses
+    }
+
+    pub fn clauses_mut(&mut self) -> &mut Vec<Box<dyn CodeGenerate>> {
+        &mut self.clauses
+    }
+}
+
+impl CodeGenerate for ControlHeader {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let spacing = info.spacing;
+        let mut result = write!(f, "{}", self.keyword);
+        if spacing.after_control_keyword {
+            result = result.and(write!(f, " "));
+        }
+        result = result.and(write!(f, "({}", spacing.inside_parens_space()));
+        for (index, clause) in self.clauses.iter().enumerate() {
+            if index != 0 {
+                result = result.and(write!(f, "; "));
+            }
+            result = result.and(clause.generate(f, info));
+        }
+        result = result.and(write!(f, "{})", spacing.inside_parens_space()));
+
+        result
+    }
+
+    fn node(&self) -> Node<'_> {
+        Node::ControlHeader(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::ControlHeader(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        boxed_children(&self.clauses)
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        boxed_children_mut(&mut self.clauses)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IfStatement {
-    content: HeaderPlusBody<JoinedCode>,
+    content: HeaderPlusBody<ControlHeader>,
 }
 
 impl IfStatement {
@@ -11,28 +97,56 @@ impl IfStatement {
     where CT: CodeGenerate + 'static {
         IfStatement {
             content: HeaderPlusBody::new(
-                JoinedCode::new(
-                    vec![
-                        Box::new(String::from("if (")),
-                        Box::new(condition),
-                        Box::new(String::from(")"))
-                    ]
-                ),
+                ControlHeader::new("if", vec![Box::new(condition)]),
                 body
             )
         }
     }
+
+    pub fn header(&self) -> &ControlHeader {
+        self.content.header()
+    }
+
+    pub fn header_mut(&mut self) -> &mut ControlHeader {
+        self.content.header_mut()
+    }
+
+    pub fn body(&self) -> &CodeBody {
+        self.content.body()
+    }
+
+    pub fn body_mut(&mut self) -> &mut CodeBody {
+        self.content.body_mut()
+    }
 }
 
 impl CodeGenerate for IfStatement {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::If;
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let info = &info.with_context(GeneratorContext::If);
         self.content.generate(f, info)
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::IfStatement(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::IfStatement(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        self.content.children()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        self.content.children_mut()
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WhileStatement {
-    content: HeaderPlusBody<JoinedCode>,
+    content: HeaderPlusBody<ControlHeader>,
 }
 
 impl WhileStatement {
@@ -40,28 +154,56 @@ impl WhileStatement {
     where CT: CodeGenerate + 'static {
         WhileStatement {
             content: HeaderPlusBody::new(
-                JoinedCode::new(
-                    vec![
-                        Box::new(String::from("while (")),
-                        Box::new(condition),
-                        Box::new(String::from(")"))
-                    ]
-                ),
+                ControlHeader::new("while", vec![Box::new(condition)]),
                 body
             )
         }
     }
+
+    pub fn header(&self) -> &ControlHeader {
+        self.content.header()
+    }
+
+    pub fn header_mut(&mut self) -> &mut ControlHeader {
+        self.content.header_mut()
+    }
+
+    pub fn body(&self) -> &CodeBody {
+        self.content.body()
+    }
+
+    pub fn body_mut(&mut self) -> &mut CodeBody {
+        self.content.body_mut()
+    }
 }
 
 impl CodeGenerate for WhileStatement {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::While;
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let info = &info.with_context(GeneratorContext::While);
         self.content.generate(f, info)
     }
+
+    fn node(&self) -> Node<'_> {
+        Node::WhileStatement(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::WhileStatement(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        self.content.children()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        self.content.children_mut()
+    }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForLoop {
-    content: HeaderPlusBody<JoinedCode>,
+    content: HeaderPlusBody<ControlHeader>,
 }
 
 impl ForLoop {
@@ -70,23 +212,51 @@ impl ForLoop {
         CT: CodeGenerate + 'static,
         UT: CodeGenerate + 'static {
         ForLoop { content: HeaderPlusBody::new(
-            JoinedCode::new(vec![
-                Box::new(String::from("for (")),
+            ControlHeader::new("for", vec![
                 Box::new(init_code),
-                Box::new(String::from("; ")),
                 Box::new(continuation_code),
-                Box::new(String::from("; ")),
                 Box::new(update_code),
-                Box::new(String::from(")")),
             ]),
             CodeBody::new(body)
         ) }
     }
+
+    pub fn header(&self) -> &ControlHeader {
+        self.content.header()
+    }
+
+    pub fn header_mut(&mut self) -> &mut ControlHeader {
+        self.content.header_mut()
+    }
+
+    pub fn body(&self) -> &CodeBody {
+        self.content.body()
+    }
+
+    pub fn body_mut(&mut self) -> &mut CodeBody {
+        self.content.body_mut()
+    }
 }
 
 impl CodeGenerate for ForLoop {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, mut info: CodeGenerationInfo) -> fmt::Result {
-        info.context = GeneratorContext::ForLoop;
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        let info = &info.with_context(GeneratorContext::ForLoop);
         self.content.generate(f, info)
     }
-}
\ No newline at end of file
+
+    fn node(&self) -> Node<'_> {
+        Node::ForLoop(self)
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::ForLoop(self)
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        self.content.children()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        self.content.children_mut()
+    }
+}
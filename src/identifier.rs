@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "char", "const", "continue", "default", "do",
+    "double", "else", "enum", "extern", "float", "for", "goto", "if", "inline",
+    "int", "long", "register", "restrict", "return", "short", "signed",
+    "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned",
+    "void", "volatile", "while", "alignas", "alignof", "bool", "constexpr",
+    "false", "nullptr", "static_assert", "thread_local", "true", "typeof",
+    "typeof_unqual",
+];
+
+const CPP_KEYWORDS: &[&str] = &[
+    "and", "and_eq", "asm", "bitand", "bitor", "catch", "char8_t", "char16_t",
+    "char32_t", "class", "compl", "concept", "consteval", "constinit",
+    "const_cast", "co_await", "co_return", "co_yield", "decltype", "delete",
+    "dynamic_cast", "explicit", "export", "friend", "mutable", "namespace",
+    "new", "noexcept", "not", "not_eq", "operator", "or", "or_eq", "private",
+    "protected", "public", "reinterpret_cast", "requires", "static_cast",
+    "template", "this", "throw", "try", "typeid", "typename", "using",
+    "virtual", "wchar_t", "xor", "xor_eq",
+];
+
+/// The language whose identifier rules names are validated against
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TargetLanguage {
+    #[default]
+    C,
+    Cpp,
+}
+
+/// Decides what happens when a generated name is not a valid identifier
+///
+/// Allow skips validation entirely. Error fails the generation. Suffix and
+/// Prefix add the given text to the generated name, and fail the generation
+/// if the result is still not a valid identifier.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IdentifierPolicy {
+    #[default]
+    Allow,
+    Error,
+    Suffix(Cow<'static, str>),
+    Prefix(Cow<'static, str>),
+}
+
+/// The reason a generated name is not a valid identifier
+#[derive(Clone, Debug, PartialEq)]
+pub enum IdentifierError {
+    Empty,
+    Keyword(String),
+    Reserved(String),
+    LeadingDigit(String),
+    InvalidCharacter(String, char),
+}
+
+impl fmt::Display for IdentifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentifierError::Empty => write!(f, "identifier is empty"),
+            IdentifierError::Keyword(name) => write!(f, "'{}' is a keyword", name),
+            IdentifierError::Reserved(name) => write!(f, "'{}' is a reserved identifier", name),
+            IdentifierError::LeadingDigit(name) => write!(f, "'{}' starts with a digit", name),
+            IdentifierError::InvalidCharacter(name, char) => {
+                write!(f, "'{}' contains the invalid character '{}'", name, char)
+            }
+        }
+    }
+}
+
+impl std::error::Error for IdentifierError {}
+
+/// Checks that a name is usable as an identifier in the target language
+///
+/// ```
+/// # use code_generator::validate_identifier;
+/// # use code_generator::IdentifierError;
+/// # use code_generator::TargetLanguage;
+/// #
+/// assert!(validate_identifier("uart_init", TargetLanguage::C).is_ok());
+/// assert!(validate_identifier("class", TargetLanguage::C).is_ok());
+/// assert_eq!(
+///     Err(IdentifierError::Keyword(String::from("class"))),
+///     validate_identifier("class", TargetLanguage::Cpp)
+/// );
+/// assert_eq!(
+///     Err(IdentifierError::Reserved(String::from("_Uart"))),
+///     validate_identifier("_Uart", TargetLanguage::C)
+/// );
+/// assert_eq!(
+///     Err(IdentifierError::LeadingDigit(String::from("2d_point"))),
+///     validate_identifier("2d_point", TargetLanguage::C)
+/// );
+/// ```
+pub fn validate_identifier(identifier: &str, language: TargetLanguage) -> Result<(), IdentifierError> {
+    let mut chars = identifier.chars();
+    let first = match chars.next() {
+        Some(first) => first,
+        None => return Err(IdentifierError::Empty),
+    };
+
+    if let Some(char) = identifier.chars().find(|char| !char.is_ascii_alphanumeric() && *char != '_') {
+        return Err(IdentifierError::InvalidCharacter(identifier.to_string(), char));
+    }
+
+    if first.is_ascii_digit() {
+        return Err(IdentifierError::LeadingDigit(identifier.to_string()));
+    }
+
+    let is_reserved = match language {
+        TargetLanguage::C => identifier.starts_with("__"),
+        TargetLanguage::Cpp => identifier.contains("__"),
+    } || (first == '_' && chars.next().is_some_and(|char| char.is_ascii_uppercase()));
+    if is_reserved {
+        return Err(IdentifierError::Reserved(identifier.to_string()));
+    }
+
+    let is_keyword = C_KEYWORDS.contains(&identifier) || match language {
+        TargetLanguage::C => false,
+        TargetLanguage::Cpp => CPP_KEYWORDS.contains(&identifier),
+    };
+    if is_keyword {
+        return Err(IdentifierError::Keyword(identifier.to_string()));
+    }
+
+    Ok(())
+}
+
+thread_local! {
+    /// The first name rejected by the identifier policy since the last call
+    /// to take_rejection
+    ///
+    /// Generation goes through fmt::Display, whose errors carry no reason, so
+    /// the reason a name was rejected is kept here for try_render.
+    static REJECTION: RefCell<Option<IdentifierError>> = const { RefCell::new(None) };
+}
+
+/// Records why a name was rejected, unless a name was already rejected
+pub(crate) fn reject(error: IdentifierError) {
+    REJECTION.with(|rejection| {
+        rejection.borrow_mut().get_or_insert(error);
+    });
+}
+
+/// Takes the recorded rejection, leaving none
+pub(crate) fn take_rejection() -> Option<IdentifierError> {
+    REJECTION.with(|rejection| rejection.borrow_mut().take())
+}
+
+/// Applies the identifier policy to a generated name
+pub(crate) fn apply_identifier_policy(
+    identifier: String,
+    language: TargetLanguage,
+    policy: &IdentifierPolicy
+) -> Result<String, IdentifierError> {
+    if *policy == IdentifierPolicy::Allow {
+        return Ok(identifier);
+    }
+
+    let error = match validate_identifier(&identifier, language) {
+        Ok(()) => return Ok(identifier),
+        Err(error) => error,
+    };
+
+    let identifier = match policy {
+        IdentifierPolicy::Allow | IdentifierPolicy::Error => return Err(error),
+        IdentifierPolicy::Suffix(suffix) => identifier + suffix,
+        IdentifierPolicy::Prefix(prefix) => format!("{}{}", prefix, identifier),
+    };
+
+    validate_identifier(&identifier, language).map(|_| identifier)
+}
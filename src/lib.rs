@@ -64,7 +64,7 @@
 //!         }
 //!     }
 //! }
-//! ", format!("{}", code.display(info)));
+//! ", format!("{}", code.display(&info)));
 //!
 //! let mut info = CodeGenerationInfo::from_style(CodeStyle::Allman);
 //! info.set_new_line_type(NewLineType::Nl);
@@ -82,7 +82,7 @@
 //!         }
 //!     }
 //! }
-//! ", format!("{}", code.display(info)));
+//! ", format!("{}", code.display(&info)));
 //!
 //!
 //!
@@ -91,17 +91,60 @@
 //! Because the output format is not dependent on the input data structure, it
 //! is very easy to make code generation modular. Any pieces of generated code
 //! that share structure, can share a generator.
+//!
+//! # Features
+//!
+//! * `derive`: `#[derive(CodeGenerate)]` for structs whose fields are
+//!   generated in order, see the code_generator_derive crate.
+//! * `macros`: `c_code!`, which builds generators from C templates with
+//!   interpolated generators, see the code_generator_derive crate.
+//! * `serde`: Serialize and Deserialize for trees of the generators of this
+//!   crate, as `Box<dyn CodeGenerate>`, and for CodeGenerationInfo. Custom
+//!   generators cannot be serialized. The context and aligned column of a
+//!   CodeGenerationInfo are only set during generation, so they are skipped.
+//! * `testing`: Snapshot, which compares the output of a generator under every
+//!   CodeStyle with golden files and shows a unified diff when they differ.
+//!   Setting `CODE_GENERATOR_BLESS=1` writes the new output instead.
 
 mod building_block_generators;
 mod setup;
 mod flow_control_generators;
 mod data_type_generators;
 mod as_case;
+mod identifier;
+mod symbol_table;
+mod config;
+mod clang_format;
+mod node;
+mod visitor;
+mod builders;
+mod writer;
+mod template;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "testing")]
+mod testing;
 
 pub use building_block_generators::*;
 pub use setup::*;
 pub use flow_control_generators::*;
 pub use data_type_generators::*;
+pub use identifier::*;
+pub use symbol_table::*;
+pub use config::*;
+pub use clang_format::*;
+pub use node::*;
+pub use visitor::*;
+pub use builders::*;
+pub use writer::*;
+pub use template::*;
+#[cfg(feature = "testing")]
+pub use testing::*;
+
+#[cfg(feature = "derive")]
+pub use code_generator_derive::CodeGenerate;
+#[cfg(feature = "macros")]
+pub use code_generator_derive::c_code;
 
 
 
@@ -114,7 +157,1049 @@ mod tests {
         let default_gen_info: CodeGenerationInfo = CodeGenerationInfo::from_style(CodeStyle::KnR);
         let case = NameType::FixedCase(CaseType::CamelCase);
         let name = Name::new_with_type("testName", case);
-        let result = format!("{}", name.display(default_gen_info));
+        let result = format!("{}", name.display(&default_gen_info));
         assert_eq!(result, "testName");
     }
+
+    #[test]
+    fn name_acronym_splitting() {
+        let case_types = CaseTypes::new();
+        assert_eq!(Name::new("HTTPServer").resolve(&case_types), "http_server");
+        assert_eq!(Name::new("getHTTP").resolve(&case_types), "get_http");
+        assert_eq!(Name::new("TEst").resolve(&case_types), "t_est");
+        assert_eq!(
+            Name::new_with_type("HTTPServer", NameType::Type).resolve(&case_types),
+            "HttpServer"
+        );
+
+        let case_types = case_types.with_acronyms(&["HTTP"]);
+        assert_eq!(
+            Name::new_with_type("httpServer", NameType::Type).resolve(&case_types),
+            "HTTPServer"
+        );
+        assert_eq!(
+            Name::new_with_type("HTTPServer", NameType::Function).resolve(&case_types),
+            "http_server"
+        );
+    }
+
+    #[test]
+    fn bypass_names_drop_word_separators() {
+        let case_types = CaseTypes::new();
+        let name = Name::new_with_type("Init", NameType::Bypass).with_prefix_parts(["drv", "uart"]);
+        assert_eq!(name.resolve(&case_types), "drvuartInit");
+        let name = Name::join(["uart", "Rx"]).with_type(NameType::Bypass);
+        assert_eq!(name.resolve(&case_types), "uartRx");
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+        assert_eq!(format!("{}", name.display(&info)), "uartRx");
+        assert_eq!(name, Name::new_with_type("uartRx", NameType::Bypass));
+        let mut names = std::collections::HashSet::new();
+        names.insert(name);
+        assert!(names.contains(&Name::new_with_type("uartRx", NameType::Bypass)));
+        assert!(!names.contains(&Name::new_with_type("uartrx", NameType::Bypass)));
+    }
+
+    #[test]
+    fn include_kebab_case_file_name() {
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_case_types(CaseTypes::new().with_file_name(CaseType::KebabCase));
+        let include = Include::new(Name::new_with_type("uartDriver", NameType::File));
+        assert_eq!(format!("{}", include.display(&info)), "#include \"uart-driver.h\"");
+    }
+
+    #[test]
+    fn struct_member_keyword_suffix() {
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl)
+            .with_case_types(CaseTypes::new().with_identifier_policy(IdentifierPolicy::Suffix("_".into())));
+        let code = Struct::new(Name::new("Config"), vec![
+            (Name::new("int"), Name::new("default")),
+            (Name::new("int"), Name::new("timeout")),
+        ]);
+        assert_eq!(
+            format!("{}", code.display(&info)),
+            "typedef struct {\n    Int default_;\n    Int timeout;\n} Config;"
+        );
+    }
+
+    #[test]
+    fn name_rejected_by_policy_fails_generation() {
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_case_types(CaseTypes::new().with_identifier_policy(IdentifierPolicy::Error));
+        let include = Include::new(Name::new_with_type("int", NameType::Default));
+        assert_eq!(format!("{}", include.display(&info)), "#error invalid identifier: 'int' is a keyword");
+        assert_eq!(format!("{}", Name::new("int`value").display(&info)), "int_value");
+
+        let rejected = Err(GenerateError::Identifier(IdentifierError::Keyword(String::from("int"))));
+        let mut output = String::new();
+        let mut w = CodeWriter::new(&mut output, info.clone());
+        w.line("start();").emit(&Name::new("int")).line("end();");
+        assert_eq!(Err(GenerateError::Identifier(IdentifierError::Keyword(String::from("int")))), w.finish());
+        assert_eq!(output, "start();\r\nend();");
+
+        assert_eq!(rejected, Name::new("int").try_render(&info));
+        let declaration = FunctionDeclaration::new(
+            Name::new_with_type("void", NameType::Bypass),
+            Name::new("uartInit"),
+            vec![(Name::new_with_type("char", NameType::Bypass), Name::new("int"))]
+        );
+        assert_eq!(rejected, declaration.try_render(&info));
+        let config = Struct::new(Name::new("Config"), vec![
+            (Name::new_with_type("char", NameType::Bypass), Name::new("int")),
+        ]);
+        assert_eq!(rejected, config.try_render(&info));
+        let enumeration = Enum::new(Name::new("Mode"), vec![(Name::new("2d"), None)]);
+        assert_eq!(
+            Err(GenerateError::Identifier(IdentifierError::LeadingDigit(String::from("2d")))),
+            enumeration.try_render(&info)
+        );
+        assert_eq!(Ok(String::from("uart_init")), Name::new("uartInit").try_render(&info));
+    }
+
+    #[test]
+    fn symbol_collisions_per_scope() {
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl);
+        let code = CodeSet::new(vec![
+            Box::new(Struct::new(Name::new("Config"), vec![
+                (Name::new("int"), Name::new("fooBar")),
+                (Name::new("int"), Name::new("foo_bar")),
+            ])),
+            Box::new(Enum::new(Name::new("Mode"), vec![
+                (Name::new("idle"), None),
+            ])),
+            Box::new(FunctionDeclaration::new(Name::new("void"), Name::new("fooBar"), vec![])),
+            Box::new(Function::new(
+                FunctionSignature::new(Name::new("void"), Name::new("fooBar"), vec![]),
+                CodeSet::new(vec![])
+            )),
+        ]);
+
+        let mut symbols = SymbolTable::new().with_disambiguation(true);
+        let output = symbols.render(&code, &info).unwrap();
+
+        assert_eq!(symbols.collisions().len(), 1);
+        assert_eq!(symbols.collisions()[0].scope, SymbolScope::Struct(String::from("Config")));
+        assert_eq!(symbols.collisions()[0].renamed_to, Some(String::from("foo_bar_2")));
+        assert!(symbols.contains(&SymbolScope::File, "Idle"));
+        assert!(symbols.contains(&SymbolScope::File, "foo_bar"));
+        assert!(output.contains("Int foo_bar_2;"));
+
+        // enum values share the file scope, and calls follow the rename
+        let mut info = info;
+        info.case_types.type_name_case = CaseType::SnakeCase;
+        let code = CodeSet::new(vec![
+            Box::new(Enum::new(Name::new("Mode"), vec![(Name::new("uartIdle"), None)])),
+            Box::new(Enum::new(Name::new("State"), vec![(Name::new("uart_idle"), None)])),
+            Box::new(FunctionDeclaration::new(Name::new("void"), Name::new("uartStop"), vec![])),
+            Box::new(FunctionDeclaration::new(Name::new("void"), Name::new("uart_stop"), vec![])),
+            Box::new(FunctionCall::new_with_end(Name::new("uart_stop"), vec![])),
+        ]);
+        let mut symbols = SymbolTable::new().with_disambiguation(true);
+        let output = symbols.render(&code, &info).unwrap();
+
+        assert_eq!(symbols.collisions().len(), 2);
+        assert_eq!(symbols.collisions()[0].scope, SymbolScope::File);
+        assert_eq!(symbols.collisions()[0].renamed_to, Some(String::from("uart_idle_2")));
+        assert!(output.contains("uart_idle_2,"));
+        assert!(output.ends_with("void uart_stop_2();\nuart_stop_2();"));
+
+        // without a table nothing is renamed
+        assert!(format!("{}", code.display(&info)).ends_with("void uart_stop();\nuart_stop();"));
+    }
+
+    #[test]
+    fn function_signature_wrapping() {
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl)
+            .with_max_line_width(40)
+            .with_list_wrap_style(ListWrapStyle::AlignToParen);
+        let short = FunctionDeclaration::new(
+            Name::new("void"),
+            Name::new("uartInit"),
+            vec![(Name::new("config"), Name::new("config"))]
+        );
+        assert_eq!(format!("{}", short.display(&info)), "Void uart_init(Config config);");
+
+        let long = FunctionDeclaration::new(
+            Name::new("void"),
+            Name::new("uartInit"),
+            vec![
+                (Name::new("config"), Name::new("config")),
+                (Name::new("callback"), Name::new("onReceive")),
+            ]
+        );
+        assert_eq!(
+            format!("{}", long.display(&info)),
+            "Void uart_init(Config config,\n               Callback on_receive);"
+        );
+
+        let call = FunctionCall::new_with_end(Name::new("uartInit"), vec![
+            Box::new(String::from("&config")),
+            Box::new(String::from("on_receive_callback")),
+        ]);
+        assert_eq!(
+            format!("{}", call.display(&info.indent())),
+            "uart_init(&config,\n              on_receive_callback);"
+        );
+
+        // the `;` of a declaration and the ` {` of a KnR function count
+        let long = FunctionDeclaration::new(
+            Name::new("void"),
+            Name::new("uartInit"),
+            vec![(Name::new("config"), Name::new("config")), (Name::new("mode"), Name::new("mode"))]
+        );
+        let single_line = "Void uart_init(Config config, Mode mode);";
+        let info = info.with_max_line_width(single_line.len());
+        assert_eq!(format!("{}", long.display(&info)), single_line);
+        let info = info.with_max_line_width(single_line.len() - 1);
+        assert_eq!(format!("{}", long.display(&info)), "Void uart_init(Config config,\n               Mode mode);");
+
+        let function = Function::new(long.signature().clone(), CodeSet::new(vec![Box::new("run();")]));
+        let info = info.with_max_line_width("Void uart_init(Config config, Mode mode) {".len());
+        assert_eq!(format!("{}", function.display(&info)), "Void uart_init(Config config, Mode mode) {\n    run();\n}");
+        let info = info.with_max_line_width("Void uart_init(Config config, Mode mode) {".len() - 1);
+        assert_eq!(
+            format!("{}", function.display(&info)),
+            "Void uart_init(Config config,\n               Mode mode) {\n    run();\n}"
+        );
+
+        let info = info.with_spacing(Spacing::new().with_inside_parens(true))
+            .with_list_wrap_style(ListWrapStyle::HangingIndent)
+            .with_max_line_width(31);
+        assert_eq!(
+            format!("{}", long.display(&info)),
+            "Void uart_init(\n    Config config, Mode mode );"
+        );
+        let info = info.with_max_line_width(30);
+        assert_eq!(
+            format!("{}", long.display(&info)),
+            "Void uart_init(\n    Config config,\n    Mode mode );"
+        );
+    }
+
+    #[test]
+    fn column_alignment() {
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl)
+            .with_column_alignment(true);
+        let code = CodeSet::new(vec![
+            Box::new(ConstDefine::new(Name::new("baud"), String::from("115200"))),
+            Box::new(ConstDefine::new(Name::new("rxBufferSize"), String::from("64"))),
+            Box::new(String::from("")),
+            Box::new(Struct::new(Name::new("Config"), vec![
+                (Name::new("u32"), Name::new("baud")),
+                (Name::new("callback"), Name::new("onReceive")),
+            ])),
+            Box::new(Enum::new(Name::new("Mode"), vec![
+                (Name::new("idle"), Some(0)),
+                (Name::new("transmit"), Some(1)),
+                (Name::new("receive"), None),
+            ])),
+            Box::new(ConstDefine::new(Name::new("x"), String::from("1"))),
+        ]);
+        assert_eq!(format!("{}", code.display(&info)), "\
+#define BAUD           115200
+#define RX_BUFFER_SIZE 64
+
+typedef struct {
+    U32      baud;
+    Callback on_receive;
+} Config;
+typedef enum {
+    Idle     = 0,
+    Transmit = 1,
+    Receive,
+} Mode;
+#define X 1");
+
+        let info = CodeGenerationInfo::from_style(CodeStyle::Default)
+            .with_new_line_type(NewLineType::Nl)
+            .with_column_alignment(true);
+        assert_eq!(format!("{}", code.display(&info)), "\
+#define BAUD\t\t\t115200
+#define RX_BUFFER_SIZE\t64
+
+typedef struct {
+\tU32\t\t\tbaud;
+\tCallback\ton_receive;
+} Config;
+typedef enum {
+\tIdle\t\t= 0,
+\tTransmit\t= 1,
+\tReceive,
+} Mode;
+#define X\t1");
+
+        // references keep their alignment, and the aligned column is no setting
+        let define = ConstDefine::new(Name::new("baud"), String::from("115200"));
+        let by_reference: &dyn CodeGenerate = &&define;
+        assert_eq!(by_reference.alignment_width(&info), Some("#define BAUD".len()));
+        let mut aligned = info.clone();
+        aligned.align_column = Some(24);
+        assert_eq!(aligned, info);
+    }
+
+    #[test]
+    fn style_from_config_file() {
+        let path = std::env::temp_dir()
+            .join(format!("code_generator_style_from_config_file_{}.toml", std::process::id()));
+        std::fs::write(&path, "\
+# Shared team style
+style = \"Allman\"
+indent_amount = 2
+max_line_width = 100
+list_wrap_style = \"align_to_paren\"
+align_columns = true
+
+[spacing]
+pointer_alignment = \"right\"
+inside_parens = true
+
+[case_types]
+type_name = \"snake_case\"
+digit_boundary = \"before\"
+identifier_policy = \"suffix:_\"
+member_name = \"custom:lower:upper:__\"
+acronyms = [\"USB\", \"I2C, SPI\"]
+").unwrap();
+        let info = CodeGenerationInfo::from_config_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(info.indent_style, IndentationStyle::Allman);
+        assert_eq!(info.indent_amount, 2);
+        assert_eq!(info.max_line_width, Some(100));
+        assert_eq!(info.list_wrap_style, ListWrapStyle::AlignToParen);
+        assert!(info.align_columns && info.align_defines);
+        assert_eq!(info.case_types.type_name_case, CaseType::SnakeCase);
+        assert_eq!(info.case_types.digit_boundary, DigitBoundary::Before);
+        assert_eq!(info.case_types.identifier_policy, IdentifierPolicy::Suffix("_".into()));
+        assert_eq!(info.case_types.member_name_case, CaseType::Custom {
+            separator: "__".into(),
+            first_chunk: ChunkCase::Lower,
+            other_chunks: ChunkCase::Upper,
+        });
+        assert_eq!(info.case_types.acronyms, vec!["USB", "I2C, SPI"]);
+        assert_eq!(info.spacing.pointer_alignment, PointerAlignment::Right);
+        assert!(info.spacing.inside_parens);
+    }
+
+    #[test]
+    fn style_config_errors_name_the_key() {
+        let error = CodeGenerationInfo::from_config_str("[case_types]\nmember_name = shouty").unwrap_err();
+        assert!(error.to_string().starts_with(
+            "line 2: invalid value 'shouty' for 'case_types.member_name', expected one of"
+        ));
+
+        let error = CodeGenerationInfo::from_config_str("indent_amount = four").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: invalid value 'four' for 'indent_amount', expected a non-negative integer"
+        );
+
+        let error = CodeGenerationInfo::from_config_str("indent_amount 4").unwrap_err();
+        assert_eq!(error.to_string(), "line 1: expected 'key = value', found 'indent_amount 4'");
+    }
+
+    #[test]
+    fn style_from_clang_format() {
+        let style = ClangFormatStyle::parse("\
+# Kernel-ish style
+---
+Language: JavaScript
+IndentWidth: 2
+---
+Language: Cpp
+BasedOnStyle: GNU
+UseTab: Never
+IndentWidth: 8
+LineEnding: LF
+PointerAlignment: Right
+SpaceBeforeParens: Always
+AlignConsecutiveMacros:
+  Enabled: true
+  AcrossEmptyLines: false
+BreakBeforeBraces: Custom
+BraceWrapping: { AfterFunction: true, AfterStruct: false }
+...
+").unwrap();
+        assert_eq!(style.info.indent_style, IndentationStyle::GNU);
+        assert_eq!(style.info.indent_type, IndentationType::Spaces);
+        assert_eq!(style.info.indent_amount, 8);
+        assert_eq!(style.info.max_line_width, Some(79));
+        assert_eq!(style.info.new_line_type, NewLineType::Nl);
+        assert!(style.info.align_defines);
+        assert!(!style.info.align_columns);
+        assert_eq!(style.info.spacing.pointer_alignment, PointerAlignment::Right);
+        assert!(style.info.spacing.before_call_paren);
+        assert_eq!(style.info.context_styles.get(GeneratorContext::Function), Some(IndentationStyle::Allman));
+        assert_eq!(style.info.context_styles.get(GeneratorContext::Struct), Some(IndentationStyle::KnR));
+        assert!(style.unsupported_keys.is_empty());
+
+        // Options only count under Custom, wherever Custom is set
+        let style = ClangFormatStyle::parse("\
+BraceWrapping:
+  AfterFunction: true
+SpaceBeforeParensOptions:
+  AfterFunctionDefinitionName: true
+BreakBeforeBraces: Attach
+").unwrap();
+        assert_eq!(style.info.context_styles.get(GeneratorContext::Function), None);
+        assert!(!style.info.spacing.before_call_paren);
+        assert_eq!(style.info.spacing.pointer_alignment, PointerAlignment::Right);
+
+        let style = ClangFormatStyle::parse("\
+BasedOnStyle: Google
+BraceWrapping:
+  AfterFunction: true
+SpaceBeforeParensOptions:
+  AfterFunctionDefinitionName: true
+BreakBeforeBraces: Custom
+SpaceBeforeParens: Custom
+").unwrap();
+        assert_eq!(style.info.context_styles.get(GeneratorContext::Function), Some(IndentationStyle::Allman));
+        assert!(style.info.spacing.before_call_paren);
+        assert_eq!(style.info.spacing.pointer_alignment, PointerAlignment::Left);
+
+        let style = ClangFormatStyle::parse("BasedOnStyle: Mozilla").unwrap();
+        assert_eq!(style.info.context_styles.get(GeneratorContext::Function), Some(IndentationStyle::Allman));
+        assert_eq!(style.info.context_styles.get(GeneratorContext::Enum), Some(IndentationStyle::Allman));
+        let style = ClangFormatStyle::parse("BasedOnStyle: WebKit").unwrap();
+        let function = Function::new(
+            FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("f"), vec![]),
+            CodeSet::new(vec![Box::new(String::from("g();"))])
+        );
+        let info = style.info.with_new_line_type(NewLineType::Nl);
+        assert_eq!(format!("{}", function.display(&info)), "void f()\n{\n    g();\n}");
+        assert_eq!(info.context_styles.get(GeneratorContext::Struct), None);
+
+        let style = ClangFormatStyle::parse("BasedOnStyle: Microsoft\nBreakBeforeBraces: Whitesmiths").unwrap();
+        assert_eq!(style.info.indent_style, IndentationStyle::Allman);
+        assert_eq!(style.unsupported_keys, vec![String::from("BreakBeforeBraces")]);
+
+        let error = ClangFormatStyle::parse("ColumnLimit: wide").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1: invalid value 'wide' for 'ColumnLimit', expected a non-negative integer"
+        );
+    }
+
+    #[test]
+    fn spacing_options() {
+        let code = Function::new(
+            FunctionSignature::new(
+                Name::new_with_type("Uart*", NameType::Bypass),
+                Name::new("uartOpen"),
+                vec![(Name::new_with_type("const char*", NameType::Bypass), Name::new("path"))]
+            ),
+            CodeSet::new(vec![
+                Box::new(WhileStatement::new(
+                    BinaryOperation::new(String::from("retries"), ">", String::from("0")),
+                    CodeBody::new(vec![
+                        Box::new(FunctionCall::new_with_end(Name::new("uartPoll"), vec![
+                            Box::new(String::from("path")),
+                        ])),
+                    ])
+                )),
+            ])
+        );
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl)
+            .with_spacing(Spacing::new()
+                .with_after_control_keyword(false)
+                .with_before_call_paren(true)
+                .with_inside_parens(true)
+                .with_around_binary_operators(false)
+                .with_pointer_alignment(PointerAlignment::Right));
+        assert_eq!(format!("{}", code.display(&info)), "\
+Uart *uart_open ( const char *path ) {
+    while( retries>0 ) {
+        uart_poll ( path );
+    }
+}");
+    }
+
+    #[test]
+    fn name_as_map_key() {
+        let mut symbols = std::collections::HashMap::new();
+        symbols.insert(Name::new("fooBar"), 1);
+        symbols.insert(Name::new_with_type("foo`bar", NameType::Function), 2);
+        symbols.insert(Name::new_with_type("FooBar", NameType::Bypass), 3);
+
+        symbols.insert(Name::new_with_type("FooBar", NameType::Function), 4);
+
+        assert_eq!(symbols.len(), 3);
+        assert_eq!(symbols.get(&Name::new("FooBar")), Some(&1));
+        assert_eq!(symbols.get(&Name::new_with_type("FooBar", NameType::Function)), Some(&4));
+        assert_eq!(symbols.get(&Name::new_with_type("FooBar", NameType::Bypass)), Some(&3));
+    }
+
+    #[test]
+    fn per_context_brace_styles() {
+        let code = Function::new(
+            FunctionSignature::new(Name::new_with_type("int", NameType::Bypass), Name::new("uartOpen"), vec![]),
+            CodeSet::new(vec![
+                Box::new(IfStatement::new(
+                    String::from("busy"),
+                    CodeBody::new(vec![Box::new(String::from("return -1;"))])
+                )),
+                Box::new(String::from("return 0;")),
+            ])
+        );
+        let expected = "\
+int uart_open()
+{
+\tif (busy) {
+\t\treturn -1;
+\t}
+\treturn 0;
+}";
+
+        let info = CodeGenerationInfo::from_style(CodeStyle::Linux)
+            .with_new_line_type(NewLineType::Nl);
+        assert_eq!(format!("{}", code.display(&info)), expected);
+
+        let info = CodeGenerationInfo::from_config_str("
+            style = Allman
+            indent_type = tabs
+            new_line_type = nl
+
+            [context_styles]
+            if = KnR
+        ").unwrap();
+        assert_eq!(format!("{}", code.display(&info)), expected);
+    }
+
+    #[test]
+    fn tab_width_and_smart_tabs() {
+        let body = || CodeBody::new(vec![Box::new(IfStatement::new(
+            String::from("ready"),
+            CodeBody::new(vec![Box::new(String::from("read();"))])
+        ))]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl)
+            .with_indent_type(IndentationType::Tabs)
+            .with_ident_amount(8)
+            .with_tab_width(8);
+        assert_eq!(format!("{}", body().display(&info)), "{\n\tif (ready) {\n\t\tread();\n\t}\n}");
+
+        let info = info.with_ident_amount(4);
+        assert_eq!(format!("{}", body().display(&info)), "{\n    if (ready) {\n\tread();\n    }\n}");
+
+        let info = info.with_ident_amount(0).with_ident_style(IndentationStyle::Horstmann);
+        assert_eq!(format!("{}", body().display(&info)), "{if (ready)\n{read();\n}\n}");
+
+        let call = FunctionCall::new_with_end(Name::new("uartWrite"), vec![
+            Box::new(String::from("port")),
+            Box::new(String::from("buffer")),
+        ]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::Default)
+            .with_new_line_type(NewLineType::Nl)
+            .with_max_line_width(20)
+            .with_list_wrap_style(ListWrapStyle::AlignToParen)
+            .indent();
+        assert_eq!(format!("{}", call.display(&info)), "uart_write(port,\n\t\t\t   buffer);");
+        let info = info.with_indent_type(IndentationType::SmartTabs);
+        assert_eq!(format!("{}", call.display(&info)), "uart_write(port,\n\t           buffer);");
+    }
+
+    #[test]
+    fn text_block_normalization() {
+        let code = CodeBody::new(vec![
+            Box::new(TextBlock::new("\tswitch (mode) {\n\tcase 1:\r\n\t    start();\n\t}\n\n")),
+        ]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::Default)
+            .with_new_line_type(NewLineType::CrNl);
+        assert_eq!(
+            format!("{}", code.display(&info)),
+            "{\r\n\tswitch (mode) {\r\n\tcase 1:\r\n\t\tstart();\r\n\t}\r\n}"
+        );
+
+        let text = TextBlock::new("  \n\n").with_trailing_new_lines(TrailingNewLines::Keep);
+        assert_eq!(format!("{}", text.display(&info)), "");
+
+        // every line is indented to the level with tabs and aligned with spaces
+        let code = CodeBody::new(vec![
+            Box::new(TextBlock::new("    if (x) {\n        y();\n    }\n  /* end */")),
+        ]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::Default)
+            .with_new_line_type(NewLineType::Nl)
+            .with_indent_type(IndentationType::SmartTabs);
+        assert_eq!(
+            format!("{}", code.display(&info)),
+            "{\n\t  if (x) {\n\t      y();\n\t  }\n\t/* end */\n}"
+        );
+    }
+
+    #[test]
+    fn context_path_follows_nesting() {
+        #[derive(Clone)]
+        struct LoopDepth;
+        impl CodeGenerate for LoopDepth {
+            fn generate(&self, f: &mut std::fmt::Formatter<'_>, info: &CodeGenerationInfo) -> std::fmt::Result {
+                let loops = info.context.depth_of(GeneratorContext::While);
+                write!(f, "/* {} loop(s), in function: {} */", loops, info.context.is_inside(GeneratorContext::Function))
+            }
+        }
+
+        let code = Function::new(
+            FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("poll"), vec![]),
+            CodeSet::new(vec![
+                Box::new(WhileStatement::new(
+                    String::from("running"),
+                    CodeBody::new(vec![Box::new(LoopDepth)])
+                )),
+            ])
+        );
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+            .with_new_line_type(NewLineType::Nl);
+        assert_eq!(format!("{}", code.display(&info)), "\
+void poll() {
+    while (running) {
+        /* 1 loop(s), in function: true */
+    }
+}");
+
+        let mut info = info.with_context(GeneratorContext::Function);
+        for _ in 0..20 {
+            info = info.with_context(GeneratorContext::While);
+        }
+        assert_eq!(info.context.depth(), 21);
+        assert_eq!(info.context.depth_of(GeneratorContext::While), 20);
+        assert!(info.context.is_inside(GeneratorContext::Function));
+    }
+
+    #[test]
+    fn gnu_brace_layout() {
+        let int = || Name::new_with_type("int", NameType::Bypass);
+        let code = CodeSet::new(vec![
+            Box::new(Struct::new(Name::new("Point"), vec![(int(), Name::new("x"))])),
+            Box::new(Enum::new(Name::new("Mode"), vec![(Name::new("idle"), None)])),
+            Box::new(Function::new(
+                FunctionSignature::new(int(), Name::new("poll"), vec![]),
+                CodeSet::new(vec![
+                    Box::new(WhileStatement::new(
+                        String::from("running"),
+                        CodeBody::new(vec![Box::new(String::from("step();"))])
+                    )),
+                    Box::new(String::from("return 0;")),
+                ])
+            )),
+        ]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::GNU)
+            .with_new_line_type(NewLineType::Nl);
+        assert_eq!(format!("{}", code.display(&info)), "\
+typedef struct
+{
+  int x;
+} Point;
+typedef enum
+{
+  Idle,
+} Mode;
+int poll()
+{
+  while (running)
+    {
+      step();
+    }
+  return 0;
+}");
+    }
+
+    #[test]
+    fn inspect_and_rewrite_tree() {
+        fn count_statements(generator: &dyn CodeGenerate) -> usize {
+            let own = match generator.node() {
+                Node::CodeBody(body) => body.items().len(),
+                _ => 0,
+            };
+            own + generator.children().into_iter().map(count_statements).sum::<usize>()
+        }
+
+        let mut code = CodeSet::new(vec![
+            Box::new(Struct::new(Name::new("config"), vec![
+                (Name::new_with_type("int", NameType::Bypass), Name::new("baudRate")),
+            ])),
+            Box::new(Function::new(
+                FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("poll"), vec![]),
+                CodeSet::new(vec![
+                    Box::new(String::from("int ready = 0;")),
+                    Box::new(WhileStatement::new(
+                        String::from("!ready"),
+                        CodeBody::new(vec![Box::new(String::from("ready = check();"))])
+                    )),
+                ])
+            )),
+        ]);
+        assert_eq!(count_statements(&code), 3);
+
+        for item in code.items_mut() {
+            match item.node_mut() {
+                NodeMut::Struct(value) => *value.name_mut() = value.name().clone().prepend("uart"),
+                NodeMut::Function(function) => {
+                    function.body_mut().items_mut().remove(0);
+                }
+                _ => (),
+            }
+        }
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+        assert_eq!(format!("{}", code.display(&info)), "\
+typedef struct {
+    int baud_rate;
+} UartConfig;
+void poll() {
+    while (!ready) {
+        ready = check();
+    }
+}");
+    }
+
+    #[test]
+    fn visitor_walks_every_generator() {
+        #[derive(Clone)]
+        struct Marker;
+        impl CodeGenerate for Marker {
+            fn generate(&self, f: &mut std::fmt::Formatter<'_>, _info: &CodeGenerationInfo) -> std::fmt::Result {
+                write!(f, "/* marker */")
+            }
+        }
+
+        #[derive(Default)]
+        struct Trace(Vec<String>);
+        impl Visitor for Trace {
+            fn visit_name(&mut self, name: &Name) {
+                self.0.push(format!("name {}", name.source()));
+            }
+            fn visit_if_statement(&mut self, _statement: &IfStatement) {
+                self.0.push(String::from("if"));
+            }
+            fn visit_text(&mut self, text: &str) {
+                self.0.push(format!("text {}", text));
+            }
+            fn visit_custom(&mut self, _generator: &dyn CodeGenerate) {
+                self.0.push(String::from("custom"));
+            }
+            fn visit_code_body(&mut self, _body: &CodeBody) {
+                self.0.push(String::from("body"));
+            }
+            fn visit_include(&mut self, include: &Include) {
+                self.0.push(format!("include {}", include.file_name().source()));
+            }
+            fn visit_parameter(&mut self, _parameter: &Parameter) {
+                self.0.push(String::from("parameter"));
+            }
+            fn visit_new_line(&mut self) {
+                self.0.push(String::from("new line"));
+            }
+        }
+
+        let code = IfStatement::new(
+            Name::new("ready"),
+            CodeBody::new(vec![Box::new(Marker), Box::new(String::from("read();"))])
+        );
+        let mut trace = Trace::default();
+        trace.visit(&code);
+        assert_eq!(trace.0, vec!["if", "name ready", "body", "custom", "text read();"]);
+
+        let code = CodeSet::new(vec![
+            Box::new(Include::new_sys("stdint.h")),
+            Box::new(NewLine::new()),
+            Box::new(FunctionDeclaration::new(
+                Name::new_with_type("void", NameType::Bypass),
+                Name::new("send"),
+                vec![(Name::new_with_type("int", NameType::Bypass), Name::new("value"))]
+            )),
+        ]);
+        let mut trace = Trace::default();
+        trace.visit(&code);
+        assert_eq!(trace.0, vec![
+            "include stdint.h", "name stdint.h", "new line", "name void", "name send", "parameter", "name int", "name value",
+        ]);
+    }
+
+    #[test]
+    fn clone_debug_and_compare_trees() {
+        #[derive(Clone)]
+        struct Marker(u32);
+        impl CodeGenerate for Marker {
+            fn generate(&self, f: &mut std::fmt::Formatter<'_>, _info: &CodeGenerationInfo) -> std::fmt::Result {
+                write!(f, "/* marker {} */", self.0)
+            }
+        }
+
+        let function = Function::new(
+            FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("reset"), vec![]),
+            CodeSet::new(vec![Box::new(ConstDefine::new(Name::new("retries"), String::from("3")))])
+        );
+        let mut renamed = function.clone();
+        assert_eq!(renamed, function);
+        *renamed.signature_mut().name_mut() = Name::new("hardReset");
+        assert_ne!(renamed, function);
+
+        let code = CodeSet::new_separated(vec![Box::new(function.clone()), Box::new(function)]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+        let single = "void reset() {\n    #define RETRIES 3\n}";
+        assert_eq!(format!("{}", code.clone().display(&info)), format!("{}\n\n{}", single, single));
+
+        let debug = format!("{:?}", code);
+        assert!(debug.starts_with("CodeSet { code_set: [Function { content: HeaderPlusBody {"));
+        assert!(debug.contains("ConstDefine { name: Name { source: \"retries\""));
+
+        let marker: Box<dyn CodeGenerate> = Box::new(Marker(1));
+        assert_eq!(format!("{:?}", marker), "Custom");
+        assert_ne!(*marker, *marker);
+        assert_ne!(*marker, *marker.clone());
+    }
+
+    #[test]
+    fn builders_match_constructors() {
+        let void = Name::new_with_type("void", NameType::Bypass);
+        let built = Function::builder("uartWrite")
+            .param("Buffer*", "data")
+            .statement(String::from("send(data);"))
+            .build();
+        let buffer = Name::new_with_type("Buffer*", NameType::Bypass);
+        let constructed = Function::new(
+            FunctionSignature::new(void.clone(), Name::new("uartWrite"), vec![(buffer, Name::new("data"))]),
+            CodeSet::new(vec![Box::new(String::from("send(data);"))])
+        );
+        assert_eq!(built, constructed);
+
+        let declaration = Function::builder(String::from("uartWrite"))
+            .storage_class(StorageClass::Extern)
+            .attribute("__attribute__((nonnull))")
+            .declaration();
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+        assert_eq!(
+            format!("{}", declaration.display(&info)),
+            "extern __attribute__((nonnull)) void uart_write();"
+        );
+
+        let built = Enum::builder("mode").value("off").numbered_value("on", 2).build();
+        assert_eq!(built, Enum::new(Name::new("mode"), vec![(Name::new("off"), None), (Name::new("on"), Some(2))]));
+        let coordinate = Name::new_with_type("Coordinate", NameType::Bypass);
+        let built = Struct::builder("point").member("Coordinate", "x").build();
+        assert_eq!(built, Struct::new(Name::new("point"), vec![(coordinate, Name::new("x"))]));
+        assert_ne!(built, Struct::builder("point").member("Coordinate", "x").comment("A point").build());
+
+        let function = Function::builder("uartSend")
+            .returns("uint32_t")
+            .param("const char*", "text")
+            .param("int", "length")
+            .comment("Sends text, see */ for the end")
+            .statement("return write(text, length);")
+            .build();
+        assert_eq!(
+            format!("{}", function.display(&info)),
+            "/* Sends text, see * / for the end */\nuint32_t uart_send(const char* text, int length) {\n    return write(text, length);\n}"
+        );
+        let value = Struct::builder("message").member("const char*", "text").member(Name::new("messageKind"), "kind").build();
+        assert_eq!(
+            format!("{}", value.display(&info)),
+            "typedef struct {\n    const char* text;\n    MessageKind kind;\n} Message;"
+        );
+    }
+
+    #[test]
+    fn writer_matches_built_tree() {
+        let signature = || FunctionSignature::new(
+            Name::new_with_type("int", NameType::Bypass),
+            Name::new("sumValues"),
+            vec![(Name::new_with_type("int", NameType::Bypass), Name::new("count"))]
+        );
+        let tree = CodeSet::new(vec![
+            Box::new(String::from("#include \"sum.h\"")),
+            Box::new(String::new()),
+            Box::new(Function::new(signature(), CodeSet::new(vec![
+                Box::new(String::from("int total = 0;")),
+                Box::new(ForLoop::new("int i = 0", "i < count", "i++", vec![
+                    Box::new(IfStatement::new("i % 2", CodeBody::new(vec![Box::new("total += i;")]))),
+                ])),
+                Box::new(String::from("return total;")),
+            ]))),
+        ]);
+
+        let styles = [
+            CodeStyle::Allman, CodeStyle::GNU, CodeStyle::KnR, CodeStyle::Horstmann, CodeStyle::Pico,
+            CodeStyle::Minimal, CodeStyle::Linux, CodeStyle::Default,
+        ];
+        for style in styles {
+            let info = CodeGenerationInfo::from_style(style).with_new_line_type(NewLineType::Nl);
+            let mut written = String::new();
+            let mut w = CodeWriter::new(&mut written, info.clone());
+            w.line("#include \"sum.h\"").blank_line();
+            w.function(signature(), |w| {
+                w.line("int total = 0;");
+                w.for_("int i = 0", "i < count", "i++", |w| {
+                    w.if_("i % 2", |w| {
+                        w.emit(&"total += i;");
+                    });
+                });
+                w.line("return total;");
+            });
+            w.finish().unwrap();
+            assert_eq!(written, format!("{}", tree.display(&info)), "{:?}", style);
+        }
+    }
+
+    #[test]
+    fn template_rendering() {
+        let template = Template::parse(
+            "#define {{name:const_define}} {{value}}\n{{#each groups}}{{#each items}}{{group}}.{{name}} {{/each}}\n{{/each}}"
+        ).unwrap();
+        let item = |name: &str| TemplateValues::new().with_name("name", name);
+        let values = TemplateValues::new()
+            .with_name("name", "maxSize")
+            .with_code("value", JoinedCode::new(vec![
+                Box::new("(1 +"), Box::new(NewLine::new()), Box::new("2)"),
+            ]))
+            .with_list("groups", vec![
+                TemplateValues::new()
+                    .with_name("group", Name::new_with_type("a", NameType::Bypass))
+                    .with_list("items", vec![item("x"), item("y")]),
+            ]);
+        let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::CrNl);
+        assert_eq!(
+            template.render(&values, &info).unwrap(),
+            "#define MAX_SIZE (1 +\r\n                 2)\r\na.x a.y \r\n"
+        );
+
+        let error = template.render(&TemplateValues::new().with_name("name", "x"), &info).unwrap_err();
+        assert_eq!(error.to_string(), "line 1: no value for 'value'");
+        let error = Template::parse("{{body:member}}").unwrap()
+            .render(&TemplateValues::new().with_code("body", "x;"), &info)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 1: the value of 'body' is not a Name");
+        assert!(Template::parse("{{/each}}").is_err());
+        assert!(Template::parse("{{name").is_err());
+
+        let template = Template::parse("int {{name}}[1][1] = {{0}};\nstruct s x[] = {{{value}}, {0}};\nint y[] = {{1}").unwrap();
+        assert_eq!(
+            template.render(&TemplateValues::new().with_name("name", "table").with_name("value", "one"), &info).unwrap(),
+            "int table[1][1] = {{0}};\r\nstruct s x[] = {one, {0}};\r\nint y[] = {{1}"
+        );
+
+        let info = info.with_case_types(CaseTypes::new().with_identifier_policy(IdentifierPolicy::Error));
+        let error = Template::parse("int {{name}};").unwrap()
+            .render(&TemplateValues::new().with_name("name", "default"), &info)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 1: could not generate 'name': invalid identifier: 'default' is a keyword");
+        let error = Template::parse("\n{{body}}").unwrap()
+            .render(&TemplateValues::new().with_code("body", Struct::new(Name::new("Config"), vec![
+                (Name::new_with_type("char", NameType::Bypass), Name::new("int")),
+            ])), &info)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "line 2: could not generate 'body': invalid identifier: 'int' is a keyword");
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn snapshot_diffs() {
+        let code = Function::new(
+            FunctionSignature::new(Name::new_with_type("int", NameType::Bypass), Name::new("getCount"), vec![]),
+            CodeSet::new(vec![Box::new("return count;")]),
+        );
+        let directory = std::env::temp_dir().join(format!("code_generator_snapshots_{}", std::process::id()));
+        let snapshot = Snapshot::new(&directory, "get_count").with_bless(false);
+
+        match snapshot.check(&code) {
+            Err(SnapshotError::Mismatch(mismatches)) => {
+                assert_eq!(CodeStyle::ALL.len() - 3, mismatches.len());
+                assert!(mismatches.iter().all(|mismatch| mismatch.diff.is_none()));
+                assert!(mismatches.iter().all(|mismatch| mismatch.style != CodeStyle::Lisp));
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        snapshot.clone().with_bless(true).check(&code).unwrap();
+        snapshot.check(&code).unwrap();
+        assert!(!snapshot.path(CodeStyle::Whitesmiths).exists());
+
+        // nothing is blessed for styles or generators which cannot be generated
+        let blessed = snapshot.clone().with_bless(true);
+        match blessed.with_styles(&[CodeStyle::Ratliff]).check(&code) {
+            Err(SnapshotError::UnsupportedStyle(CodeStyle::Ratliff)) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(!snapshot.path(CodeStyle::Ratliff).exists());
+
+        #[derive(Clone)]
+        struct Failing;
+        impl CodeGenerate for Failing {
+            fn generate(&self, _f: &mut std::fmt::Formatter<'_>, _info: &CodeGenerationInfo) -> std::fmt::Result {
+                Err(std::fmt::Error)
+            }
+        }
+        let failing = Snapshot::new(&directory, "failing").with_bless(true);
+        match failing.check(&Failing) {
+            Err(SnapshotError::Generate { style: CodeStyle::Allman, error: GenerateError::Failed }) => (),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert!(!failing.path(CodeStyle::Allman).exists());
+
+        std::fs::write(snapshot.path(CodeStyle::Linux), "int get_count(void)\r\n{\r\n\treturn count;\r\n}").unwrap();
+        match snapshot.check(&code) {
+            Err(SnapshotError::Mismatch(mismatches)) => {
+                assert_eq!(1, mismatches.len());
+                assert_eq!(Some(String::from(
+                    "--- expected\n+++ actual\n@@ -1,4 +1,4 @@\n-int get_count(void)\n+int get_count()\n {\n \treturn count;\n }\n"
+                )), mismatches[0].diff);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!("", unified_diff("a\nb", "a\nb", 3));
+        assert_eq!(
+            "--- expected\n+++ actual\n@@ -2,3 +2,2 @@\n 2\n-3\n 4\n@@ -8,2 +7,3 @@\n 8\n+8.5\n 9\n",
+            unified_diff("1\n2\n3\n4\n5\n6\n7\n8\n9", "1\n2\n4\n5\n6\n7\n8\n8.5\n9", 1)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let tree: Box<dyn CodeGenerate> = Box::new(CodeSet::new_separated(vec![
+            Box::new(Enum::new(Name::new("uartMode"), vec![
+                (Name::new("idle"), None),
+                (Name::new("busy"), Some(4)),
+            ])),
+            Box::new(Struct::new(Name::new("uartConfig"), vec![
+                (Name::new_with_type("uint32_t", NameType::Bypass), Name::new("baudRate")),
+            ])),
+            Box::new(Function::new(
+                FunctionSignature::new(Name::new_with_type("void", NameType::Bypass), Name::new("uartInit"), vec![]),
+                CodeSet::new(vec![Box::new(IfStatement::new(
+                    Name::new_with_type("HW_READY", NameType::FixedCase(CaseType::ScreamingSnakeCase)),
+                    CodeBody::new(vec![Box::new(String::from("start();"))])
+                ))])
+            )),
+        ]));
+        let json = serde_json::to_string(&tree).unwrap();
+        let restored: Box<dyn CodeGenerate> = serde_json::from_str(&json).unwrap();
+        assert_eq!(*restored, *tree);
+
+        let info = CodeGenerationInfo::from_style(CodeStyle::Linux)
+            .with_context_style(GeneratorContext::Struct, IndentationStyle::Allman)
+            .with_new_line_type(NewLineType::Nl);
+        let json = serde_json::to_string(&info.with_context(GeneratorContext::Function)).unwrap();
+        assert!(!json.contains("\"context\"") && !json.contains("\"align_column\""));
+        let restored_info: CodeGenerationInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored_info, info);
+        assert_eq!(
+            format!("{}", restored.display(&restored_info)),
+            format!("{}", tree.display(&info))
+        );
+
+        #[derive(Clone)]
+        struct Marker;
+        impl CodeGenerate for Marker {
+            fn generate(&self, f: &mut std::fmt::Formatter<'_>, _info: &CodeGenerationInfo) -> std::fmt::Result {
+                write!(f, "/* marker */")
+            }
+        }
+        let custom: Box<dyn CodeGenerate> = Box::new(CodeSet::new(vec![Box::new(Marker)]));
+        let error = serde_json::to_string(&custom).unwrap_err();
+        assert!(error.to_string().contains("a custom CodeGenerate cannot be serialized"));
+    }
 }
@@ -0,0 +1,224 @@
+use std::fmt;
+use crate::building_block_generators::*;
+use crate::data_type_generators::*;
+use crate::flow_control_generators::*;
+use crate::setup::*;
+
+/// A view of a generator, which tells what kind of code it generates
+///
+/// Every generator of this crate returns its own kind from
+/// CodeGenerate::node, and generators from other crates are Custom. Together
+/// with CodeGenerate::children, this allows walking a built tree without
+/// rendering it.
+///
+/// ```
+/// # use code_generator::CodeBody;
+/// # use code_generator::CodeGenerate;
+/// # use code_generator::CodeSet;
+/// # use code_generator::Function;
+/// # use code_generator::FunctionCall;
+/// # use code_generator::FunctionSignature;
+/// # use code_generator::Name;
+/// # use code_generator::Node;
+/// #
+/// fn function_names(generator: &dyn CodeGenerate, names: &mut Vec<String>) {
+///     if let Node::Function(function) = generator.node() {
+///         names.push(function.signature().name().source().to_string());
+///     }
+///     for child in generator.children() {
+///         function_names(child, names);
+///     }
+/// }
+///
+/// let code = CodeSet::new(vec![
+///     Box::new(Function::new(
+///         FunctionSignature::new(Name::new("void"), Name::new("uartInit"), vec![]),
+///         CodeSet::new(vec![Box::new(FunctionCall::new_with_end(Name::new("clockEnable"), vec![]))])
+///     )),
+///     Box::new(Function::new(
+///         FunctionSignature::new(Name::new("void"), Name::new("uartDeinit"), vec![]),
+///         CodeSet::new(vec![])
+///     )),
+/// ]);
+/// let mut names = Vec::new();
+/// function_names(&code, &mut names);
+/// assert_eq!(vec!["uartInit", "uartDeinit"], names);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename = "Generator"))]
+pub enum Node<'a> {
+    Indentation,
+    NewLine,
+    Name(&'a Name),
+    Text(&'a str),
+    TextBlock(&'a TextBlock),
+    Include(&'a Include),
+    CodeSet(&'a CodeSet),
+    JoinedCode(&'a JoinedCode),
+    SeparatedCode(&'a SeparatedCode),
+    BinaryOperation(&'a BinaryOperation),
+    ParameterList(&'a ParameterList),
+    CodeBody(&'a CodeBody),
+    HeaderPlusBody { header: &'a dyn CodeGenerate, body: &'a CodeBody },
+    ControlHeader(&'a ControlHeader),
+    IfStatement(&'a IfStatement),
+    WhileStatement(&'a WhileStatement),
+    ForLoop(&'a ForLoop),
+    Parameter(&'a Parameter),
+    FunctionSignature(&'a FunctionSignature),
+    FunctionDeclaration(&'a FunctionDeclaration),
+    Function(&'a Function),
+    FunctionCall(&'a FunctionCall),
+    HeaderFile(&'a HeaderFile),
+    Enum(&'a Enum),
+    Struct(&'a Struct),
+    TypeDef(&'a TypeDef),
+    ConstDefine { name: &'a Name, value: &'a dyn CodeGenerate },
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::serde_support::custom_generator"))]
+    Custom,
+}
+
+/// A mutable view of a generator, see Node
+///
+/// ```
+/// # use code_generator::CodeGenerate;
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeSet;
+/// # use code_generator::ConstDefine;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Name;
+/// # use code_generator::NewLineType;
+/// # use code_generator::NodeMut;
+/// #
+/// fn prefix_defines(generator: &mut dyn CodeGenerate) {
+///     if let NodeMut::ConstDefine { name, .. } = generator.node_mut() {
+///         *name = name.clone().prepend("uart");
+///     }
+///     for child in generator.children_mut() {
+///         prefix_defines(child);
+///     }
+/// }
+///
+/// let mut code = CodeSet::new(vec![
+///     Box::new(ConstDefine::new(Name::new("baudRate"), String::from("9600"))),
+///     Box::new(ConstDefine::new(Name::new("parity"), String::from("0"))),
+/// ]);
+/// prefix_defines(&mut code);
+/// let info = CodeGenerationInfo::new().with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+///     "#define UART_BAUD_RATE 9600\n#define UART_PARITY 0",
+///     format!("{}", code.display(&info))
+/// );
+/// ```
+pub enum NodeMut<'a> {
+    Indentation,
+    NewLine,
+    Name(&'a mut Name),
+    Text(&'a mut String),
+    TextBlock(&'a mut TextBlock),
+    Include(&'a mut Include),
+    CodeSet(&'a mut CodeSet),
+    JoinedCode(&'a mut JoinedCode),
+    SeparatedCode(&'a mut SeparatedCode),
+    BinaryOperation(&'a mut BinaryOperation),
+    ParameterList(&'a mut ParameterList),
+    CodeBody(&'a mut CodeBody),
+    HeaderPlusBody { header: &'a mut dyn CodeGenerate, body: &'a mut CodeBody },
+    ControlHeader(&'a mut ControlHeader),
+    IfStatement(&'a mut IfStatement),
+    WhileStatement(&'a mut WhileStatement),
+    ForLoop(&'a mut ForLoop),
+    Parameter(&'a mut Parameter),
+    FunctionSignature(&'a mut FunctionSignature),
+    FunctionDeclaration(&'a mut FunctionDeclaration),
+    Function(&'a mut Function),
+    FunctionCall(&'a mut FunctionCall),
+    HeaderFile(&'a mut HeaderFile),
+    Enum(&'a mut Enum),
+    Struct(&'a mut Struct),
+    TypeDef(&'a mut TypeDef),
+    ConstDefine { name: &'a mut Name, value: &'a mut dyn CodeGenerate },
+    Custom,
+}
+
+/// Calls a macro with the Node kinds which wrap a single generator
+macro_rules! single_generator_kinds {
+    ($macro:ident) => {
+        $macro!(
+            Name, Text, TextBlock, Include, CodeSet, JoinedCode, SeparatedCode,
+            BinaryOperation, ParameterList, CodeBody, ControlHeader, IfStatement,
+            WhileStatement, ForLoop, Parameter, FunctionSignature,
+            FunctionDeclaration, Function, FunctionCall, HeaderFile, Enum, Struct,
+            TypeDef
+        )
+    };
+}
+
+impl fmt::Debug for dyn CodeGenerate + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        macro_rules! debug {
+            ($($kind:ident),*) => {
+                match self.node() {
+                    $(Node::$kind(node) => fmt::Debug::fmt(node, f),)*
+                    Node::Indentation => fmt::Debug::fmt(&Indentation::new(), f),
+                    Node::NewLine => fmt::Debug::fmt(&NewLine::new(), f),
+                    Node::HeaderPlusBody { header, body } => f.debug_struct("HeaderPlusBody")
+                        .field("header", &header)
+                        .field("body", body)
+                        .finish(),
+                    Node::ConstDefine { name, value } => f.debug_struct("ConstDefine")
+                        .field("name", name)
+                        .field("value", &value)
+                        .finish(),
+                    Node::Custom => write!(f, "Custom"),
+                }
+            };
+        }
+        single_generator_kinds!(debug)
+    }
+}
+
+/// Structural equality of generators
+///
+/// Generators are equal when they are of the same kind and their contents are
+/// equal. Custom generators are never equal, not even to themselves, since
+/// their contents cannot be inspected.
+///
+/// ```
+/// # use code_generator::CodeGenerate;
+/// # use code_generator::CodeSet;
+/// # use code_generator::IfStatement;
+/// # use code_generator::CodeBody;
+/// #
+/// let code: Box<dyn CodeGenerate> = Box::new(IfStatement::new(
+///     String::from("ready"),
+///     CodeBody::new(vec![Box::new("read();")])
+/// ));
+/// let copy = code.clone();
+/// assert_eq!(&copy, &code);
+/// assert_eq!(
+///     CodeSet::new(vec![code.clone(), code]),
+///     CodeSet::new(vec![copy.clone(), copy])
+/// );
+/// ```
+impl PartialEq for dyn CodeGenerate + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        macro_rules! eq {
+            ($($kind:ident),*) => {
+                match (self.node(), other.node()) {
+                    $((Node::$kind(node), Node::$kind(other)) => node == other,)*
+                    (Node::Indentation, Node::Indentation) | (Node::NewLine, Node::NewLine) => true,
+                    (
+                        Node::HeaderPlusBody { header, body },
+                        Node::HeaderPlusBody { header: other_header, body: other_body }
+                    ) => header == other_header && body == other_body,
+                    (
+                        Node::ConstDefine { name, value },
+                        Node::ConstDefine { name: other_name, value: other_value }
+                    ) => name == other_name && value == other_value,
+                    _ => false,
+                }
+            };
+        }
+        single_generator_kinds!(eq)
+    }
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::building_block_generators::*;
+use crate::data_type_generators::*;
+use crate::flow_control_generators::*;
+use crate::setup::*;
+
+/// Serializes a generator tagged with its Node kind
+///
+/// Custom generators cannot be serialized, since they cannot be deserialized
+/// back into the same type.
+impl Serialize for dyn CodeGenerate + '_ {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.node().serialize(serializer)
+    }
+}
+
+/// The error returned when serializing a Node::Custom
+pub(crate) fn custom_generator<S: Serializer>(_serializer: S) -> Result<S::Ok, S::Error> {
+    Err(serde::ser::Error::custom(
+        "a custom CodeGenerate cannot be serialized, only the generators of this crate can"
+    ))
+}
+
+/// The owned counterpart of Node, which generators are deserialized into
+#[derive(Deserialize)]
+#[serde(rename = "Generator")]
+enum Generator {
+    Indentation,
+    NewLine,
+    Name(Name),
+    Text(String),
+    TextBlock(TextBlock),
+    Include(Include),
+    CodeSet(CodeSet),
+    JoinedCode(JoinedCode),
+    SeparatedCode(SeparatedCode),
+    BinaryOperation(BinaryOperation),
+    ParameterList(ParameterList),
+    CodeBody(CodeBody),
+    HeaderPlusBody { header: Box<dyn CodeGenerate>, body: CodeBody },
+    ControlHeader(ControlHeader),
+    IfStatement(IfStatement),
+    WhileStatement(WhileStatement),
+    ForLoop(ForLoop),
+    Parameter(Parameter),
+    FunctionSignature(FunctionSignature),
+    FunctionDeclaration(FunctionDeclaration),
+    Function(Function),
+    FunctionCall(FunctionCall),
+    HeaderFile(HeaderFile),
+    Enum(Enum),
+    Struct(Struct),
+    TypeDef(TypeDef),
+    ConstDefine { name: Name, value: Box<dyn CodeGenerate> },
+}
+
+impl<'de> Deserialize<'de> for Box<dyn CodeGenerate> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Generator::deserialize(deserializer)? {
+            Generator::Indentation => Box::new(Indentation::new()),
+            Generator::NewLine => Box::new(NewLine::new()),
+            Generator::Name(name) => Box::new(name),
+            Generator::Text(text) => Box::new(text),
+            Generator::TextBlock(text_block) => Box::new(text_block),
+            Generator::Include(include) => Box::new(include),
+            Generator::CodeSet(set) => Box::new(set),
+            Generator::JoinedCode(joined) => Box::new(joined),
+            Generator::SeparatedCode(separated) => Box::new(separated),
+            Generator::BinaryOperation(operation) => Box::new(operation),
+            Generator::ParameterList(list) => Box::new(list),
+            Generator::CodeBody(body) => Box::new(body),
+            Generator::HeaderPlusBody { header, body } => Box::new(HeaderPlusBody::new(header, body)),
+            Generator::ControlHeader(header) => Box::new(header),
+            Generator::IfStatement(statement) => Box::new(statement),
+            Generator::WhileStatement(statement) => Box::new(statement),
+            Generator::ForLoop(statement) => Box::new(statement),
+            Generator::Parameter(parameter) => Box::new(parameter),
+            Generator::FunctionSignature(signature) => Box::new(signature),
+            Generator::FunctionDeclaration(declaration) => Box::new(declaration),
+            Generator::Function(function) => Box::new(function),
+            Generator::FunctionCall(call) => Box::new(call),
+            Generator::HeaderFile(header_file) => Box::new(header_file),
+            Generator::Enum(value) => Box::new(value),
+            Generator::Struct(value) => Box::new(value),
+            Generator::TypeDef(type_def) => Box::new(type_def),
+            Generator::ConstDefine { name, value } => Box::new(ConstDefine::new(name, value)),
+        })
+    }
+}
@@ -1,9 +1,16 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::fmt;
+use std::fmt::Write;
+use crate::identifier::{reject, take_rejection, IdentifierError, IdentifierPolicy, TargetLanguage};
+use crate::node::{Node, NodeMut};
 
-#[derive(Clone, Copy, Debug)]
-
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IndentationStyle {
     Allman,
+    /// Braces of statement bodies are indented one level, and their body one
+    /// more. Braces of functions, structs and enums are not indented.
     GNU,
     Whitesmiths,
     KnR,
@@ -14,7 +21,57 @@ pub enum IndentationStyle {
     None,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl IndentationStyle {
+    /// Checks if bodies can be generated in this style
+    ///
+    /// Whitesmiths, Ratliff and Lisp bodies are not implemented yet.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            IndentationStyle::Allman | IndentationStyle::GNU | IndentationStyle::KnR |
+            IndentationStyle::Horstmann | IndentationStyle::Pico | IndentationStyle::None => true,
+            IndentationStyle::Whitesmiths | IndentationStyle::Ratliff | IndentationStyle::Lisp => false,
+        }
+    }
+}
+
+/// The capitalization of a single word in a custom case type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChunkCase {
+    Lower,
+    Upper,
+    Capitalized,
+}
+
+/// The case used when generating a name
+///
+/// The Custom variant joins words with the given separator, and applies the
+/// first_chunk capitalization to the first word and other_chunks to the rest.
+///
+/// ```
+/// # use code_generator::CaseType;
+/// # use code_generator::ChunkCase;
+/// # use code_generator::CaseTypes;
+/// # use code_generator::Name;
+/// # use code_generator::NameType;
+/// #
+/// let case_types = CaseTypes::new();
+/// let name = |case| Name::new_with_type("uartInit", NameType::FixedCase(case));
+/// assert_eq!("uart-init", name(CaseType::KebabCase).resolve(&case_types));
+/// assert_eq!("UART-INIT", name(CaseType::ScreamingKebabCase).resolve(&case_types));
+/// assert_eq!("Uart-Init", name(CaseType::TrainCase).resolve(&case_types));
+/// assert_eq!("Uart_Init", name(CaseType::AdaCase).resolve(&case_types));
+/// assert_eq!("uart.init", name(CaseType::DotCase).resolve(&case_types));
+///
+/// let custom = CaseType::Custom {
+///     separator: "::".into(),
+///     first_chunk: ChunkCase::Upper,
+///     other_chunks: ChunkCase::Capitalized,
+/// };
+/// assert_eq!("UART::Init", name(custom).resolve(&case_types));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CaseType {
     FlatCase,
     ScreamingCase,
@@ -22,15 +79,47 @@ pub enum CaseType {
     PascalCase,
     SnakeCase,
     ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+    TrainCase,
+    AdaCase,
+    DotCase,
+    Custom {
+        separator: Cow<'static, str>,
+        first_chunk: ChunkCase,
+        other_chunks: ChunkCase,
+    },
+}
+
+/// Decides how digits split the words of a name
+///
+/// Attached keeps digits in the word they follow ("Uart2Tx" is "uart2_tx"),
+/// Before starts a new word at the digits ("uart_2_tx" and "uart_2tx" for
+/// "uart2tx"), and Around makes the digits a word of their own.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DigitBoundary {
+    #[default]
+    Attached,
+    Before,
+    Around,
 }
 
-#[derive(Copy, Clone)]
+/// What indentation and alignment are written with
+///
+/// Tabs uses tabs wherever a tab stop is reached, and spaces for the rest.
+/// SmartTabs uses tabs for indentation only, and spaces for continuation
+/// lines and aligned columns, so the code looks right at any tab width.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IndentationType {
     Spaces,
     Tabs,
+    SmartTabs,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum NewLineType {
     Cr,
     Nl,
@@ -38,21 +127,120 @@ pub enum NewLineType {
     None,
 }
 
-#[derive(Clone, Copy, Debug)]
-pub enum CodeStyle {
-    Allman,
-    GNU,
-    Whitesmiths,
-    KnR,
-    Ratliff,
-    Horstmann,
-    Pico,
-    Lisp,
-    Minimal,
-    Default,
+/// Declares CodeStyle together with the list of all its presets, so a new
+/// preset cannot be left out of the list
+macro_rules! code_styles {
+    ($($style:ident),* $(,)?) => {
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub enum CodeStyle {
+            $($style,)*
+        }
+
+        impl CodeStyle {
+            /// Every preset, in declaration order
+            pub const ALL: [CodeStyle; [$(CodeStyle::$style),*].len()] = [$(CodeStyle::$style),*];
+        }
+    };
+}
+
+code_styles!(Allman, GNU, Whitesmiths, KnR, Ratliff, Horstmann, Pico, Lisp, Minimal, Linux, Default);
+
+/// The layout used when a parameter or argument list is too long to fit on
+/// one line
+///
+/// OnePerLine puts every item on its own line, indented one level deeper,
+/// with the closing parenthesis on its own line. AlignToParen keeps the first
+/// item after the opening parenthesis and aligns the others with it.
+/// HangingIndent breaks after the opening parenthesis and fills continuation
+/// lines, indented one level deeper, with as many items as fit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ListWrapStyle {
+    #[default]
+    OnePerLine,
+    AlignToParen,
+    HangingIndent,
+}
+
+/// Where the `*` of a pointer type goes in a declaration
+///
+/// Left gives `int* value`, Right gives `int *value` and Middle gives
+/// `int * value`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerAlignment {
+    #[default]
+    Left,
+    Right,
+    Middle,
+}
+
+/// Spacing options used by the generators
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spacing {
+    /// `if (x)` instead of `if(x)`, also used by while and for
+    pub after_control_keyword: bool,
+    /// `call (x)` instead of `call(x)`, also used by function signatures
+    pub before_call_paren: bool,
+    /// `if ( x )` and `call( x )` instead of `if (x)` and `call(x)`
+    pub inside_parens: bool,
+    /// `a + b` instead of `a+b`
+    pub around_binary_operators: bool,
+    pub pointer_alignment: PointerAlignment,
 }
 
-#[derive(Copy, Clone, PartialEq)]
+impl Default for Spacing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Spacing {
+    pub fn new() -> Spacing {
+        Spacing {
+            after_control_keyword: true,
+            before_call_paren: false,
+            inside_parens: false,
+            around_binary_operators: true,
+            pointer_alignment: PointerAlignment::Left,
+        }
+    }
+
+    pub fn with_after_control_keyword(mut self, enabled: bool) -> Self {
+        self.after_control_keyword = enabled;
+        self
+    }
+
+    pub fn with_before_call_paren(mut self, enabled: bool) -> Self {
+        self.before_call_paren = enabled;
+        self
+    }
+
+    pub fn with_inside_parens(mut self, enabled: bool) -> Self {
+        self.inside_parens = enabled;
+        self
+    }
+
+    pub fn with_around_binary_operators(mut self, enabled: bool) -> Self {
+        self.around_binary_operators = enabled;
+        self
+    }
+
+    pub fn with_pointer_alignment(mut self, pointer_alignment: PointerAlignment) -> Self {
+        self.pointer_alignment = pointer_alignment;
+        self
+    }
+
+    /// The space written inside parentheses
+    pub fn inside_parens_space(&self) -> &'static str {
+        if self.inside_parens { " " } else { "" }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GeneratorContext {
     If,
     While,
@@ -64,7 +252,131 @@ pub enum GeneratorContext {
     Other,
 }
 
-#[derive(Copy, Clone)]
+/// The contexts a generator is nested in, from the outermost to the current
+///
+/// ```
+/// # use code_generator::ContextPath;
+/// # use code_generator::GeneratorContext;
+/// #
+/// let path = ContextPath::new()
+///     .with(GeneratorContext::Function)
+///     .with(GeneratorContext::While)
+///     .with(GeneratorContext::If);
+/// assert_eq!(GeneratorContext::If, path.current());
+/// assert_eq!(Some(GeneratorContext::While), path.parent());
+/// assert!(path.is_inside(GeneratorContext::Function));
+/// assert!(!path.is_inside(GeneratorContext::Struct));
+/// assert_eq!(3, path.depth());
+/// assert_eq!(1, path.depth_of(GeneratorContext::While));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextPath {
+    contexts: Vec<GeneratorContext>,
+}
+
+impl ContextPath {
+    /// Creates an empty path, whose current context is File
+    pub fn new() -> ContextPath {
+        ContextPath { contexts: Vec::new() }
+    }
+
+    pub fn with(mut self, context: GeneratorContext) -> Self {
+        self.push(context);
+        self
+    }
+
+    /// Enters a nested context
+    pub fn push(&mut self, context: GeneratorContext) {
+        self.contexts.push(context);
+    }
+
+    /// The innermost context, or File when no context was entered
+    pub fn current(&self) -> GeneratorContext {
+        self.iter().next().unwrap_or(GeneratorContext::File)
+    }
+
+    /// The context the current context is nested in
+    pub fn parent(&self) -> Option<GeneratorContext> {
+        self.iter().nth(1)
+    }
+
+    /// Checks if the current context or one of its parents is `context`
+    pub fn is_inside(&self, context: GeneratorContext) -> bool {
+        self.iter().any(|entered| entered == context)
+    }
+
+    /// The contexts the current context is nested in, innermost first
+    pub fn parents(&self) -> impl Iterator<Item = GeneratorContext> + '_ {
+        self.iter().skip(1)
+    }
+
+    /// The entered contexts, innermost first
+    pub fn iter(&self) -> impl Iterator<Item = GeneratorContext> + '_ {
+        self.contexts.iter().rev().copied()
+    }
+
+    /// The number of contexts entered
+    pub fn depth(&self) -> usize {
+        self.contexts.len()
+    }
+
+    /// The number of times `context` was entered, e.g. the loop nesting depth
+    pub fn depth_of(&self, context: GeneratorContext) -> usize {
+        self.iter().filter(|entered| *entered == context).count()
+    }
+}
+
+/// Indentation styles which override the indent_style of the generation
+/// info for specific contexts
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextStyles {
+    if_statement: Option<IndentationStyle>,
+    while_statement: Option<IndentationStyle>,
+    for_loop: Option<IndentationStyle>,
+    function: Option<IndentationStyle>,
+    file: Option<IndentationStyle>,
+    struct_type: Option<IndentationStyle>,
+    enum_type: Option<IndentationStyle>,
+    other: Option<IndentationStyle>,
+}
+
+impl ContextStyles {
+    pub fn new() -> ContextStyles {
+        ContextStyles::default()
+    }
+
+    fn style_mut(&mut self, context: GeneratorContext) -> &mut Option<IndentationStyle> {
+        match context {
+            GeneratorContext::If => &mut self.if_statement,
+            GeneratorContext::While => &mut self.while_statement,
+            GeneratorContext::ForLoop => &mut self.for_loop,
+            GeneratorContext::Function => &mut self.function,
+            GeneratorContext::File => &mut self.file,
+            GeneratorContext::Struct => &mut self.struct_type,
+            GeneratorContext::Enum => &mut self.enum_type,
+            GeneratorContext::Other => &mut self.other,
+        }
+    }
+
+    pub fn with(mut self, context: GeneratorContext, style: IndentationStyle) -> Self {
+        self.set(context, Some(style));
+        self
+    }
+
+    pub fn set(&mut self, context: GeneratorContext, style: Option<IndentationStyle>) {
+        *self.style_mut(context) = style;
+    }
+
+    pub fn get(&self, context: GeneratorContext) -> Option<IndentationStyle> {
+        let mut styles = *self;
+        *styles.style_mut(context)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CaseTypes {
     pub const_define_case: CaseType,
     pub function_name_case: CaseType,
@@ -72,6 +384,16 @@ pub struct CaseTypes {
     pub type_name_case: CaseType,
     pub file_name_case: CaseType,
     pub default_case: CaseType,
+    pub digit_boundary: DigitBoundary,
+    pub acronyms: Vec<String>,
+    pub target_language: TargetLanguage,
+    pub identifier_policy: IdentifierPolicy,
+}
+
+impl Default for CaseTypes {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CaseTypes {
@@ -82,7 +404,11 @@ impl CaseTypes {
             member_name_case: CaseType::SnakeCase,
             type_name_case: CaseType::PascalCase,
             file_name_case: CaseType::PascalCase,
-            default_case: CaseType::SnakeCase
+            default_case: CaseType::SnakeCase,
+            digit_boundary: DigitBoundary::Attached,
+            acronyms: Vec::new(),
+            target_language: TargetLanguage::C,
+            identifier_policy: IdentifierPolicy::Allow,
         }
     }
 
@@ -115,46 +441,233 @@ impl CaseTypes {
         self.default_case = case_type;
         self
     }
+
+    /// Sets how digits split the words of a name
+    ///
+    /// ```
+    /// # use code_generator::CaseTypes;
+    /// # use code_generator::DigitBoundary;
+    /// # use code_generator::Name;
+    /// #
+    /// let name = Name::new("Uart2Tx");
+    /// assert_eq!("uart2_tx", name.resolve(&CaseTypes::new()));
+    ///
+    /// let case_types = CaseTypes::new().with_digit_boundary(DigitBoundary::Before);
+    /// assert_eq!("uart_2_tx", name.resolve(&case_types));
+    ///
+    /// let name = Name::new("uart2tx");
+    /// assert_eq!("uart_2tx", name.resolve(&case_types));
+    ///
+    /// let case_types = CaseTypes::new().with_digit_boundary(DigitBoundary::Around);
+    /// assert_eq!("uart_2_tx", name.resolve(&case_types));
+    /// ```
+    pub fn with_digit_boundary(mut self, digit_boundary: DigitBoundary) -> Self {
+        self.digit_boundary = digit_boundary;
+        self
+    }
+
+    /// Registers words which stay uppercase in Pascal and Camel case output
+    ///
+    /// ```
+    /// # use code_generator::CaseType;
+    /// # use code_generator::CaseTypes;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// let case_types = CaseTypes::new().with_acronyms(&["USB", "CRC", "DMA"]);
+    /// let name = Name::new_with_type("usb`crcInit", NameType::Type);
+    /// assert_eq!("USBCRCInit", name.resolve(&case_types));
+    ///
+    /// let name = Name::new_with_type("UsbDmaInit", NameType::FixedCase(CaseType::CamelCase));
+    /// assert_eq!("usbDMAInit", name.resolve(&case_types));
+    /// ```
+    pub fn with_acronyms<I>(mut self, acronyms: I) -> Self
+    where I: IntoIterator, I::Item: AsRef<str> {
+        self.acronyms = acronyms.into_iter().map(|acronym| acronym.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn with_target_language(mut self, target_language: TargetLanguage) -> Self {
+        self.target_language = target_language;
+        self
+    }
+
+    /// Sets how names which are not valid identifiers are handled
+    ///
+    /// The policy is applied to every generated name except file names and
+    /// Bypass names. A name rejected by the policy makes generation fail:
+    /// DisplayExt::try_render returns the reason, and DisplayExt::display
+    /// writes it as an `#error` directive, see DisplayHandler.
+    ///
+    /// ```
+    /// # use code_generator::CaseTypes;
+    /// # use code_generator::IdentifierError;
+    /// # use code_generator::IdentifierPolicy;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// #
+    /// let name = Name::new_with_type("default", NameType::Member);
+    ///
+    /// let case_types = CaseTypes::new().with_identifier_policy(IdentifierPolicy::Suffix("_".into()));
+    /// assert_eq!(Ok(String::from("default_")), name.try_resolve(&case_types));
+    ///
+    /// let case_types = CaseTypes::new().with_identifier_policy(IdentifierPolicy::Prefix("m_".into()));
+    /// assert_eq!(Ok(String::from("m_default")), name.try_resolve(&case_types));
+    ///
+    /// let case_types = CaseTypes::new().with_identifier_policy(IdentifierPolicy::Error);
+    /// assert_eq!(
+    ///     Err(IdentifierError::Keyword(String::from("default"))),
+    ///     name.try_resolve(&case_types)
+    /// );
+    /// ```
+    pub fn with_identifier_policy(mut self, identifier_policy: IdentifierPolicy) -> Self {
+        self.identifier_policy = identifier_policy;
+        self
+    }
+
+    /// Checks if a word is one of the registered acronyms, ignoring case
+    pub fn is_acronym(&self, word: &str) -> bool {
+        self.acronyms.iter().any(|acronym| acronym.eq_ignore_ascii_case(word))
+    }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeGenerationInfo {
     pub indent_level: usize,
     pub indent_type:  IndentationType,
     pub indent_amount: usize,
+    pub tab_width: usize,
     pub indent_style: IndentationStyle,
     pub new_line_type: NewLineType,
-    pub context: GeneratorContext,
+    /// The contexts being generated, which only exist during generation
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub context: ContextPath,
     pub case_types: CaseTypes,
+    pub max_line_width: Option<usize>,
+    pub list_wrap_style: ListWrapStyle,
+    /// Aligns struct member names and enum values
+    pub align_columns: bool,
+    /// Aligns consecutive defines
+    pub align_defines: bool,
+    /// The column the generator being aligned pads to, set by CodeSet
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) align_column: Option<usize>,
+    pub spacing: Spacing,
+    pub context_styles: ContextStyles,
 }
 
+/// Compares the settings, leaving out the aligned column, which only exists
+/// while an aligned item is generated
+impl PartialEq for CodeGenerationInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.indent_level == other.indent_level
+            && self.indent_type == other.indent_type
+            && self.indent_amount == other.indent_amount
+            && self.tab_width == other.tab_width
+            && self.indent_style == other.indent_style
+            && self.new_line_type == other.new_line_type
+            && self.context == other.context
+            && self.case_types == other.case_types
+            && self.max_line_width == other.max_line_width
+            && self.list_wrap_style == other.list_wrap_style
+            && self.align_columns == other.align_columns
+            && self.align_defines == other.align_defines
+            && self.spacing == other.spacing
+            && self.context_styles == other.context_styles
+    }
+}
+
+/// Displays the code of a generator
+///
+/// Generation which fails does not make formatting fail, since format!
+/// panics on errors. The code is replaced by an `#error` directive with the
+/// reason, so the output does not compile, and the reason is kept for error.
+///
+/// ```
+/// # use code_generator::CaseTypes;
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::GenerateError;
+/// # use code_generator::IdentifierError;
+/// # use code_generator::IdentifierPolicy;
+/// # use code_generator::Name;
+/// #
+/// let info = CodeGenerationInfo::new()
+///     .with_case_types(CaseTypes::new().with_identifier_policy(IdentifierPolicy::Error));
+/// let name = Name::new("int");
+/// let handler = name.display(&info);
+/// assert_eq!("#error invalid identifier: 'int' is a keyword", format!("{}", handler));
+/// assert_eq!(
+///     Some(GenerateError::Identifier(IdentifierError::Keyword(String::from("int")))),
+///     handler.error()
+/// );
+/// ```
 pub struct DisplayHandler<'a> {
     generator: &'a dyn CodeGenerate,
     info: CodeGenerationInfo,
+    error: RefCell<Option<GenerateError>>,
 }
 
 impl<'a> DisplayHandler<'a> {
     pub fn new(gen: &'a dyn CodeGenerate, info: CodeGenerationInfo) -> DisplayHandler<'a> {
-        DisplayHandler { generator: gen, info: info }
+        DisplayHandler { generator: gen, info, error: RefCell::new(None) }
+    }
+
+    /// Why the code could not be generated the last time it was displayed
+    pub fn error(&self) -> Option<GenerateError> {
+        self.error.borrow().clone()
     }
 }
 
 impl fmt::Display for DisplayHandler<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = try_render_generator(self.generator, &self.info);
+        let output = match &result {
+            Ok(code) => f.write_str(code),
+            Err(error) => write!(f, "#error {}", error),
+        };
+        *self.error.borrow_mut() = result.err();
+        output
+    }
+}
+
+/// Displays a generator, passing its errors on, unlike DisplayHandler
+struct Generating<'a> {
+    generator: &'a dyn CodeGenerate,
+    info: &'a CodeGenerationInfo,
+}
+
+impl fmt::Display for Generating<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.generator.generate(f, self.info)
     }
 }
 
+impl Default for CodeGenerationInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CodeGenerationInfo {
     pub fn new() -> CodeGenerationInfo {
         CodeGenerationInfo {
             indent_level: 0,
             indent_amount: 4,
+            tab_width: 4,
             indent_type: IndentationType::Spaces,
             indent_style: IndentationStyle::Allman,
             new_line_type: NewLineType::CrNl,
-            context: GeneratorContext::File,
+            context: ContextPath::new(),
             case_types: CaseTypes::new(),
+            max_line_width: None,
+            list_wrap_style: ListWrapStyle::OnePerLine,
+            align_columns: false,
+            align_defines: false,
+            align_column: None,
+            spacing: Spacing::new(),
+            context_styles: ContextStyles::new(),
         }
     }
 
@@ -168,6 +681,12 @@ impl CodeGenerationInfo {
         self
     }
 
+    /// Sets the number of columns a tab advances to
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
     pub fn with_indent_type(mut self, indent_type: IndentationType) -> Self {
         self.indent_type = indent_type;
         self
@@ -183,111 +702,228 @@ impl CodeGenerationInfo {
         self
     }
 
+    /// Sets the width parameter and argument lists are wrapped at
+    pub fn with_max_line_width(mut self, max_line_width: usize) -> Self {
+        self.max_line_width = Some(max_line_width);
+        self
+    }
+
+    pub fn with_list_wrap_style(mut self, list_wrap_style: ListWrapStyle) -> Self {
+        self.list_wrap_style = list_wrap_style;
+        self
+    }
+
+    /// Overrides the indentation style used for the bodies of one context
+    ///
+    /// ```
+    /// # use code_generator::CodeBody;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeSet;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::Function;
+    /// # use code_generator::FunctionSignature;
+    /// # use code_generator::GeneratorContext;
+    /// # use code_generator::IfStatement;
+    /// # use code_generator::IndentationStyle;
+    /// # use code_generator::Name;
+    /// # use code_generator::NewLineType;
+    /// #
+    /// let code = Function::new(
+    ///     FunctionSignature::new(Name::new("void"), Name::new("poll"), vec![]),
+    ///     CodeSet::new(vec![Box::new(IfStatement::new(
+    ///         String::from("ready"),
+    ///         CodeBody::new(vec![Box::new(String::from("read();"))])
+    ///     ))])
+    /// );
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+    ///     .with_new_line_type(NewLineType::Nl)
+    ///     .with_context_style(GeneratorContext::Function, IndentationStyle::Allman);
+    /// assert_eq!(
+    ///     "Void poll()\n{\n    if (ready) {\n        read();\n    }\n}",
+    ///     format!("{}", code.display(&info))
+    /// );
+    /// ```
+    pub fn with_context_style(mut self, context: GeneratorContext, style: IndentationStyle) -> Self {
+        self.context_styles.set(context, Some(style));
+        self
+    }
+
+    /// The indentation style used for the current context
+    pub fn current_indent_style(&self) -> IndentationStyle {
+        self.context_styles.get(self.context.current()).unwrap_or(self.indent_style)
+    }
+
+    pub fn with_spacing(mut self, spacing: Spacing) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Aligns struct member names, enum values and consecutive defines
+    pub fn with_column_alignment(mut self, align_columns: bool) -> Self {
+        self.align_columns = align_columns;
+        self.align_defines = align_columns;
+        self
+    }
+
+    /// Aligns consecutive defines, leaving struct members and enum values as
+    /// they are
+    pub fn with_define_alignment(mut self, align_defines: bool) -> Self {
+        self.align_defines = align_defines;
+        self
+    }
+
     pub fn from_style(code_style: CodeStyle) -> CodeGenerationInfo {
         match code_style {
-            CodeStyle::Allman => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
-                indent_style: IndentationStyle::Allman,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
-            },
+            CodeStyle::Allman => CodeGenerationInfo::new(),
             CodeStyle::GNU => CodeGenerationInfo {
-                indent_level: 0,
                 indent_amount: 2,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::GNU,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Horstmann => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::Horstmann,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::KnR => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::KnR,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Lisp => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::Lisp,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Minimal => CodeGenerationInfo {
-                indent_level: 0,
                 indent_amount: 0,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::None,
                 new_line_type: NewLineType::None,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Pico => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::Pico,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Ratliff => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::Ratliff,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Whitesmiths => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
-                indent_type: IndentationType::Spaces,
                 indent_style: IndentationStyle::Whitesmiths,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
+            },
+            CodeStyle::Linux => CodeGenerationInfo {
+                indent_type: IndentationType::Tabs,
+                indent_amount: 8,
+                tab_width: 8,
+                indent_style: IndentationStyle::KnR,
+                max_line_width: Some(80),
+                spacing: Spacing::new().with_pointer_alignment(PointerAlignment::Right),
+                context_styles: ContextStyles::new()
+                    .with(GeneratorContext::Function, IndentationStyle::Allman),
+                ..CodeGenerationInfo::new()
             },
             CodeStyle::Default => CodeGenerationInfo {
-                indent_level: 0,
-                indent_amount: 4,
                 indent_type: IndentationType::Tabs,
                 indent_style: IndentationStyle::KnR,
-                new_line_type: NewLineType::CrNl,
-                context: GeneratorContext::File,
-                case_types: CaseTypes::new(),
+                ..CodeGenerationInfo::new()
             },
         }
     }
 
+    /// The number of columns taken by the indentation of the current level
+    pub fn indentation_width(&self) -> usize {
+        self.indent_amount * self.indent_level
+    }
+
+    /// The column aligned items are padded to, given the widest item
+    ///
+    /// When aligning with tabs, the column is the first tab stop after the
+    /// widest item.
+    pub fn alignment_column(&self, max_width: usize) -> usize {
+        match self.indent_type {
+            IndentationType::Spaces | IndentationType::SmartTabs => max_width + 1,
+            IndentationType::Tabs => (max_width / self.effective_tab_width() + 1) * self.effective_tab_width(),
+        }
+    }
+
+    /// The padding which moves from `width` to the aligned `column`
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeStyle;
+    /// # use code_generator::IndentationType;
+    /// #
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
+    /// assert_eq!("   ", info.alignment_padding(5, info.alignment_column(7)));
+    ///
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::Default);
+    /// assert_eq!("\t\t", info.alignment_padding(5, info.alignment_column(9)));
+    ///
+    /// let info = info.with_indent_type(IndentationType::SmartTabs);
+    /// assert_eq!("     ", info.alignment_padding(5, info.alignment_column(9)));
+    /// ```
+    pub fn alignment_padding(&self, width: usize, column: usize) -> String {
+        let tab_width = self.effective_tab_width();
+        match self.indent_type {
+            IndentationType::Spaces | IndentationType::SmartTabs => {
+                " ".repeat(column.saturating_sub(width).max(1))
+            }
+            IndentationType::Tabs => "\t".repeat((column / tab_width).saturating_sub(width / tab_width).max(1)),
+        }
+    }
+
+    /// The whitespace which moves from column `from` to column `to` of a line
+    ///
+    /// Tabs are only used with IndentationType::Tabs, as the padding is used
+    /// for alignment.
+    ///
+    /// ```
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::CodeStyle;
+    /// #
+    /// let info = CodeGenerationInfo::from_style(CodeStyle::Default);
+    /// assert_eq!("\t\t  ", info.column_padding(3, 10));
+    /// assert_eq!("", info.column_padding(10, 3));
+    /// ```
+    pub fn column_padding(&self, from: usize, to: usize) -> String {
+        let tab_width = self.effective_tab_width();
+        let mut padding = String::new();
+        let mut column = from;
+        if self.indent_type == IndentationType::Tabs {
+            while (column / tab_width + 1) * tab_width <= to {
+                padding.push('\t');
+                column = (column / tab_width + 1) * tab_width;
+            }
+        }
+        padding.push_str(&" ".repeat(to.saturating_sub(column)));
+        padding
+    }
+
+    /// The column an aligned generator pads to, see
+    /// CodeGenerate::alignment_width
+    pub fn align_column(&self) -> Option<usize> {
+        self.align_column
+    }
+
+    /// The tab width, treating a width of 0 as 1
+    pub(crate) fn effective_tab_width(&self) -> usize {
+        self.tab_width.max(1)
+    }
+
     pub fn indent(&self) -> CodeGenerationInfo {
-        let mut info = *self;
+        let mut info = self.clone();
         info.indent_level += 1;
 
         info
     }
 
-    pub fn with_context(&mut self, context: GeneratorContext) -> CodeGenerationInfo {
-        let mut info = *self;
-        info.context = context;
+    /// The generation info of a nested context
+    ///
+    /// The context is pushed on the context path, so the contexts it is
+    /// nested in are still known.
+    pub fn with_context(&self, context: GeneratorContext) -> CodeGenerationInfo {
+        let mut info = self.clone();
+        info.context.push(context);
 
         info
     }
@@ -297,7 +933,15 @@ impl CodeGenerationInfo {
     }
 }
 
-pub trait CodeGenerate {
+/// A generator of code
+///
+/// Generators must implement Clone, so trees of boxed generators can be
+/// cloned. This is a breaking change from 0.1.x, where generators which do
+/// not implement Clone could implement CodeGenerate.
+///
+/// Debug and PartialEq of `dyn CodeGenerate` use the Node kind of the
+/// generator, so custom generators do not need to implement them.
+pub trait CodeGenerate: CloneGenerate {
     /// Trait function which allows the generation of code with context
     /// 
     /// # Example Implementation
@@ -310,36 +954,229 @@ pub trait CodeGenerate {
     /// # use code_generator::CodeGenerate;
     /// # use std::fmt;
     /// #
+    /// #[derive(Clone)]
     /// struct Example {
     ///     a: u32,
     /// }
     /// 
     /// impl CodeGenerate for Example {
-    ///     fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    ///     fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
     ///         write!(f, "{}", self.a)
     ///     }
     /// }
     /// 
     /// let example = Example {a: 69};
     /// let info = CodeGenerationInfo::from_style(CodeStyle::KnR);
-    /// assert_eq!("69", format!("{}", example.display(info)));
+    /// assert_eq!("69", format!("{}", example.display(&info)));
     /// ```
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result;
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result;
+
+    /// The width of the part of this generator which is padded when aligning
+    /// consecutive generators into columns
+    ///
+    /// Generators which return a width are aligned by a CodeSet when define
+    /// alignment is enabled. They receive the column to pad to from the
+    /// align_column method of the generation info.
+    fn alignment_width(&self, _info: &CodeGenerationInfo) -> Option<usize> {
+        None
+    }
+
+    /// The kind of this generator, see Node
+    ///
+    /// The default implementation returns Node::Custom.
+    fn node(&self) -> Node<'_> {
+        Node::Custom
+    }
+
+    /// The kind of this generator, allowing it to be modified
+    ///
+    /// The default implementation returns NodeMut::Custom.
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        NodeMut::Custom
+    }
+
+    /// The generators this generator contains
+    ///
+    /// The default implementation returns no children.
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        Vec::new()
+    }
+
+    /// The generators this generator contains, allowing them to be modified
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        Vec::new()
+    }
+}
+
+/// Clones generators behind a `Box<dyn CodeGenerate>`
+///
+/// This is implemented for every generator which implements Clone, which is
+/// required to implement CodeGenerate.
+pub trait CloneGenerate {
+    fn clone_boxed<'a>(&self) -> Box<dyn CodeGenerate + 'a> where Self: 'a;
 }
 
+impl<T: CodeGenerate + Clone> CloneGenerate for T {
+    fn clone_boxed<'a>(&self) -> Box<dyn CodeGenerate + 'a> where Self: 'a {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn CodeGenerate + '_> {
+    fn clone(&self) -> Self {
+        (**self).clone_boxed()
+    }
+}
+
+/// Generates the referenced generator
+///
+/// node_mut and children_mut need the generator to be mutable, so they keep
+/// their defaults.
 impl<T: CodeGenerate> CodeGenerate for &T {
-    fn generate(&self, f: &mut fmt::Formatter<'_>, info: CodeGenerationInfo) -> fmt::Result {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
         (*self).generate(f, info)
     }
+
+    fn alignment_width(&self, info: &CodeGenerationInfo) -> Option<usize> {
+        (*self).alignment_width(info)
+    }
+
+    fn node(&self) -> Node<'_> {
+        (*self).node()
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        (*self).children()
+    }
+}
+
+impl CodeGenerate for Box<dyn CodeGenerate> {
+    fn generate(&self, f: &mut fmt::Formatter<'_>, info: &CodeGenerationInfo) -> fmt::Result {
+        (**self).generate(f, info)
+    }
+
+    fn alignment_width(&self, info: &CodeGenerationInfo) -> Option<usize> {
+        (**self).alignment_width(info)
+    }
+
+    fn node(&self) -> Node<'_> {
+        (**self).node()
+    }
+
+    fn node_mut(&mut self) -> NodeMut<'_> {
+        (**self).node_mut()
+    }
+
+    fn children(&self) -> Vec<&dyn CodeGenerate> {
+        (**self).children()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn CodeGenerate> {
+        (**self).children_mut()
+    }
 }
 
 pub trait DisplayExt {
-    fn display(self: &Self, info: CodeGenerationInfo) -> DisplayHandler;
+    fn display(&self, info: &CodeGenerationInfo) -> DisplayHandler<'_>;
+
+    /// Generates the code into a String
+    ///
+    /// Unlike `format!("{}", x.display(info))`, which writes an `#error`
+    /// directive when generation fails, this returns why it failed, like the
+    /// name rejected by the identifier policy.
+    ///
+    /// ```
+    /// # use code_generator::CaseTypes;
+    /// # use code_generator::CodeGenerationInfo;
+    /// # use code_generator::DisplayExt;
+    /// # use code_generator::GenerateError;
+    /// # use code_generator::IdentifierError;
+    /// # use code_generator::IdentifierPolicy;
+    /// # use code_generator::Name;
+    /// # use code_generator::NameType;
+    /// # use code_generator::Struct;
+    /// #
+    /// let info = CodeGenerationInfo::new()
+    ///     .with_case_types(CaseTypes::new().with_identifier_policy(IdentifierPolicy::Error));
+    /// let code = Struct::new(Name::new("Config"), vec![
+    ///     (Name::new_with_type("int", NameType::Bypass), Name::new("default")),
+    /// ]);
+    /// assert_eq!(
+    ///     Err(GenerateError::Identifier(IdentifierError::Keyword(String::from("default")))),
+    ///     code.try_render(&info)
+    /// );
+    /// ```
+    fn try_render(&self, info: &CodeGenerationInfo) -> Result<String, GenerateError>;
 }
 
 impl<T> DisplayExt for T
 where T: CodeGenerate {
-    fn display(self: &Self, info: CodeGenerationInfo) -> DisplayHandler {
-        DisplayHandler::new(self, info)
+    fn display(&self, info: &CodeGenerationInfo) -> DisplayHandler<'_> {
+        DisplayHandler::new(self, info.clone())
+    }
+
+    fn try_render(&self, info: &CodeGenerationInfo) -> Result<String, GenerateError> {
+        try_render_generator(self, info)
     }
-}
\ No newline at end of file
+}
+
+/// DisplayExt::try_render for generators behind a reference
+pub(crate) fn try_render_generator(generator: &dyn CodeGenerate, info: &CodeGenerationInfo) -> Result<String, GenerateError> {
+    // a generator may render code while an outer generation failed
+    let outer = take_rejection();
+    let result = render(generator, info);
+    let rejection = take_rejection();
+    if let Some(outer) = outer {
+        reject(outer);
+    }
+
+    result.map_err(|_| match rejection {
+        Some(error) => GenerateError::Identifier(error),
+        None => GenerateError::Failed,
+    })
+}
+
+/// Generates code into a String
+///
+/// Generators use this instead of format! and display, which panic or
+/// write an `#error` directive when generation fails, so the error reaches
+/// the caller.
+pub(crate) fn render(generator: &dyn CodeGenerate, info: &CodeGenerationInfo) -> Result<String, fmt::Error> {
+    let mut output = String::new();
+    write!(output, "{}", Generating { generator, info })?;
+    Ok(output)
+}
+
+/// The reason generating code failed
+#[derive(Clone, Debug, PartialEq)]
+pub enum GenerateError {
+    /// A name was rejected by the identifier policy
+    Identifier(IdentifierError),
+    /// A generator failed without a reason, like a custom generator which
+    /// returned fmt::Error, or the output could not be written
+    Failed,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GenerateError::Identifier(error) => write!(f, "invalid identifier: {}", error),
+            GenerateError::Failed => write!(f, "a generator failed"),
+        }
+    }
+}
+
+impl std::error::Error for GenerateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GenerateError::Identifier(error) => Some(error),
+            GenerateError::Failed => None,
+        }
+    }
+}
+
+impl From<IdentifierError> for GenerateError {
+    fn from(error: IdentifierError) -> Self {
+        GenerateError::Identifier(error)
+    }
+}
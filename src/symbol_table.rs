@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use crate::building_block_generators::Name;
+use crate::setup::*;
+
+/// The scope a symbol is declared in
+///
+/// Struct scopes are identified by the generated name of the type. Enum
+/// values are declared in the scope enclosing the enum, like C does.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolScope {
+    File,
+    Struct(String),
+}
+
+impl fmt::Display for SymbolScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolScope::File => write!(f, "file scope"),
+            SymbolScope::Struct(name) => write!(f, "struct {}", name),
+        }
+    }
+}
+
+/// Two different names which generate the same identifier in one scope
+#[derive(Clone, Debug)]
+pub struct SymbolCollision {
+    pub scope: SymbolScope,
+    pub identifier: String,
+    pub existing: Name,
+    pub colliding: Name,
+    pub renamed_to: Option<String>,
+}
+
+impl fmt::Display for SymbolCollision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "'{}' and '{}' both generate '{}' in {}",
+            self.existing.source(),
+            self.colliding.source(),
+            self.identifier,
+            self.scope
+        )?;
+        if let Some(renamed_to) = &self.renamed_to {
+            write!(f, ", renamed to '{}'", renamed_to)?;
+        }
+        Ok(())
+    }
+}
+
+/// Registry of the identifiers declared by a tree of generators
+///
+/// The table is consulted while a tree is rendered by render: functions,
+/// structs and their members, enums and their values, defines and typedefs
+/// declare their names as they are generated. Names are compared after case
+/// conversion, so "fooBar" and "foo_bar" collide when both generate
+/// "foo_bar". Declaring the same name twice (a declaration and a definition)
+/// is not a collision.
+///
+/// When disambiguation is enabled, a colliding name is renamed by appending
+/// a number. Every equal name generated after the declaration in its scope
+/// or a nested one uses the new name, so references like function calls
+/// follow the rename.
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeSet;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::ConstDefine;
+/// # use code_generator::Name;
+/// # use code_generator::NameType;
+/// # use code_generator::NewLineType;
+/// # use code_generator::SymbolTable;
+/// #
+/// let code = CodeSet::new(vec![
+///     Box::new(ConstDefine::new(Name::new("fooBar"), String::from("1"))),
+///     Box::new(ConstDefine::new(Name::new("foo_bar"), String::from("2"))),
+///     Box::new(ConstDefine::new(Name::new("alias"), Name::new_with_type("foo_bar", NameType::ConstDefine))),
+/// ]);
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR)
+///     .with_new_line_type(NewLineType::Nl);
+///
+/// let mut symbols = SymbolTable::new();
+/// assert_eq!(
+///     Ok(String::from("#define FOO_BAR 1\n#define FOO_BAR 2\n#define ALIAS FOO_BAR")),
+///     symbols.render(&code, &info)
+/// );
+/// assert_eq!(1, symbols.collisions().len());
+/// assert_eq!(
+///     "'fooBar' and 'foo_bar' both generate 'FOO_BAR' in file scope",
+///     symbols.collisions()[0].to_string()
+/// );
+///
+/// let mut symbols = SymbolTable::new().with_disambiguation(true);
+/// assert_eq!(
+///     Ok(String::from("#define FOO_BAR 1\n#define FOO_BAR_2 2\n#define ALIAS FOO_BAR_2")),
+///     symbols.render(&code, &info)
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct SymbolTable {
+    symbols: HashMap<(SymbolScope, String), Name>,
+    renames: HashMap<(SymbolScope, Name), Name>,
+    scopes: Vec<SymbolScope>,
+    collisions: Vec<SymbolCollision>,
+    disambiguate: bool,
+}
+
+thread_local! {
+    /// The table consulted by the generators while SymbolTable::render runs
+    static ACTIVE: RefCell<Option<SymbolTable>> = const { RefCell::new(None) };
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SymbolTable {
+    pub fn new() -> SymbolTable {
+        SymbolTable {
+            symbols: HashMap::new(),
+            renames: HashMap::new(),
+            scopes: vec![SymbolScope::File],
+            collisions: Vec::new(),
+            disambiguate: false,
+        }
+    }
+
+    pub fn with_disambiguation(mut self, disambiguate: bool) -> Self {
+        self.disambiguate = disambiguate;
+        self
+    }
+
+    /// Generates code while declaring its names in this table
+    ///
+    /// The table keeps the symbols of earlier renders, so several files can
+    /// share one table.
+    pub fn render(&mut self, generator: &dyn CodeGenerate, info: &CodeGenerationInfo) -> Result<String, GenerateError> {
+        let outer = ACTIVE.with(|active| active.replace(Some(std::mem::take(self))));
+        let result = try_render_generator(generator, info);
+        *self = ACTIVE.with(|active| active.replace(outer)).unwrap_or_default();
+        result
+    }
+
+    /// The scope symbols are currently declared in
+    pub fn current_scope(&self) -> SymbolScope {
+        self.scopes.last().cloned().unwrap_or(SymbolScope::File)
+    }
+
+    /// Declares a name in the current scope
+    ///
+    /// If the name collides with a different name, the collision is recorded
+    /// and, when disambiguation is enabled, the name is renamed.
+    fn declare(&mut self, name: &Name, case_types: &CaseTypes) {
+        let scope = self.current_scope();
+        if self.renames.contains_key(&(scope.clone(), name.clone())) {
+            return;
+        }
+        let identifier = name.resolve(case_types);
+
+        let existing = match self.symbols.get(&(scope.clone(), identifier.clone())) {
+            None => {
+                self.symbols.insert((scope, identifier), name.clone());
+                return;
+            }
+            Some(existing) if existing == name => return,
+            Some(existing) => existing.clone(),
+        };
+
+        let mut renamed_to = None;
+        if self.disambiguate {
+            let mut index = 2;
+            let renamed = loop {
+                let candidate = name.clone().append(index.to_string());
+                let candidate_identifier = candidate.resolve(case_types);
+                if !self.symbols.contains_key(&(scope.clone(), candidate_identifier)) {
+                    break candidate;
+                }
+                index += 1;
+            };
+            let renamed_identifier = renamed.resolve(case_types);
+            self.symbols.insert((scope.clone(), renamed_identifier.clone()), renamed.clone());
+            self.renames.insert((scope.clone(), name.clone()), renamed);
+            renamed_to = Some(renamed_identifier);
+        }
+
+        self.collisions.push(SymbolCollision {
+            scope,
+            identifier,
+            existing,
+            colliding: name.clone(),
+            renamed_to,
+        });
+    }
+
+    /// The new name of a renamed name, looked up from the current scope out
+    fn renamed(&self, name: &Name) -> Option<Name> {
+        self.scopes.iter()
+            .rev()
+            .find_map(|scope| self.renames.get(&(scope.clone(), name.clone())))
+            .cloned()
+    }
+
+    /// Checks if an identifier is declared in the given scope
+    pub fn contains(&self, scope: &SymbolScope, identifier: &str) -> bool {
+        self.symbols.contains_key(&(scope.clone(), identifier.to_string()))
+    }
+
+    pub fn collisions(&self) -> &[SymbolCollision] {
+        &self.collisions
+    }
+
+    pub fn has_collisions(&self) -> bool {
+        !self.collisions.is_empty()
+    }
+}
+
+/// Declares a name in the table being rendered with, if there is one
+pub(crate) fn declare_symbol(name: &Name, case_types: &CaseTypes) {
+    ACTIVE.with(|active| {
+        if let Some(table) = active.borrow_mut().as_mut() {
+            table.declare(name, case_types);
+        }
+    });
+}
+
+/// The name a declared name is generated as, which differs from the name
+/// when it was renamed to disambiguate it
+pub(crate) fn renamed_symbol(name: &Name) -> Option<Name> {
+    ACTIVE.with(|active| active.borrow().as_ref().and_then(|table| table.renamed(name)))
+}
+
+/// The identifier a declared name generates, see renamed_symbol
+pub(crate) fn resolve_symbol(name: &Name, case_types: &CaseTypes) -> String {
+    renamed_symbol(name).as_ref().unwrap_or(name).resolve(case_types)
+}
+
+/// Declares the names generated by a closure in a nested scope
+pub(crate) fn in_symbol_scope<R>(scope: SymbolScope, generate: impl FnOnce() -> R) -> R {
+    let is_active = ACTIVE.with(|active| match active.borrow_mut().as_mut() {
+        Some(table) => {
+            table.scopes.push(scope);
+            true
+        }
+        None => false,
+    });
+    let result = generate();
+    if is_active {
+        ACTIVE.with(|active| {
+            if let Some(table) = active.borrow_mut().as_mut() {
+                table.scopes.pop();
+            }
+        });
+    }
+    result
+}
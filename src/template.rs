@@ -0,0 +1,431 @@
+use std::fmt;
+use std::path::Path;
+use crate::building_block_generators::*;
+use crate::setup::*;
+
+/// An error found while loading or rendering a template
+#[derive(Debug)]
+pub enum TemplateError {
+    Io(std::io::Error),
+    Syntax { line: usize, message: String },
+    MissingValue { line: usize, key: String },
+    WrongValue { line: usize, key: String, expected: String },
+    Generate { line: usize, key: String, error: GenerateError },
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::Io(error) => write!(f, "could not read template: {}", error),
+            TemplateError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+            TemplateError::MissingValue { line, key } => write!(f, "line {}: no value for '{}'", line, key),
+            TemplateError::WrongValue { line, key, expected } => write!(
+                f,
+                "line {}: the value of '{}' is not {}",
+                line, key, expected
+            ),
+            TemplateError::Generate { line, key, error } => write!(
+                f,
+                "line {}: could not generate '{}': {}",
+                line, key, error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TemplateError::Io(error) => Some(error),
+            TemplateError::Generate { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TemplateError {
+    fn from(error: std::io::Error) -> Self {
+        TemplateError::Io(error)
+    }
+}
+
+/// The name types which can follow the key of a placeholder
+const NAME_TYPES: &[(&str, NameType)] = &[
+    ("default", NameType::Default),
+    ("const_define", NameType::ConstDefine),
+    ("type", NameType::Type),
+    ("member", NameType::Member),
+    ("function", NameType::Function),
+    ("file", NameType::File),
+    ("bypass", NameType::Bypass),
+];
+
+/// A value inserted into a template
+#[derive(Clone, Debug)]
+pub enum TemplateValue {
+    Name(Name),
+    Code(Box<dyn CodeGenerate>),
+    List(Vec<TemplateValues>),
+}
+
+/// The values inserted into a template, by key
+#[derive(Clone, Debug, Default)]
+pub struct TemplateValues {
+    values: Vec<(String, TemplateValue)>,
+}
+
+impl TemplateValues {
+    pub fn new() -> TemplateValues {
+        TemplateValues { values: Vec::new() }
+    }
+
+    pub fn with_name(self, key: impl Into<String>, name: impl Into<Name>) -> Self {
+        self.with_value(key, TemplateValue::Name(name.into()))
+    }
+
+    pub fn with_code(self, key: impl Into<String>, code: impl CodeGenerate + 'static) -> Self {
+        self.with_value(key, TemplateValue::Code(Box::new(code)))
+    }
+
+    /// Sets the values of an each section, which are looked up before the
+    /// values around the section
+    pub fn with_list(self, key: impl Into<String>, items: Vec<TemplateValues>) -> Self {
+        self.with_value(key, TemplateValue::List(items))
+    }
+
+    /// Sets a value, replacing the value of the key if it was already set
+    pub fn with_value(mut self, key: impl Into<String>, value: TemplateValue) -> Self {
+        let key = key.into();
+        self.values.retain(|(existing, _)| *existing != key);
+        self.values.push((key, value));
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&TemplateValue> {
+        self.values.iter()
+            .find(|(existing, _)| existing == key)
+            .map(|(_, value)| value)
+    }
+}
+
+/// A part of a parsed template
+#[derive(Clone, Debug)]
+enum Part {
+    Text(String),
+    Value { line: usize, key: String, name_type: Option<NameType> },
+    Each { line: usize, key: String, body: Vec<Part> },
+}
+
+/// A text template with placeholders for names and code
+///
+/// `{{key}}` inserts the value of the key. A Name is cased by its own
+/// NameType, or by the NameType after the key, like `{{name:function}}`
+/// (`default`, `const_define`, `type`, `member`, `function`, `file` or
+/// `bypass`). Code is generated with the generation info, and the lines after
+/// its first one are indented to the column of the placeholder.
+///
+/// `{{#each key}}` ... `{{/each}}` repeats its content for every item of a
+/// list. The values of the item are looked up before the values around the
+/// section. Section tags which are alone on their line do not leave an empty
+/// line behind.
+///
+/// Keys start with a letter or `_` and contain letters, digits, `_`, `-` and
+/// `.`. Braces around anything else are kept as text, so C initializers like
+/// `{{0}}` need no escaping.
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeSet;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::IfStatement;
+/// # use code_generator::CodeBody;
+/// # use code_generator::Name;
+/// # use code_generator::NameType;
+/// # use code_generator::NewLineType;
+/// # use code_generator::Template;
+/// # use code_generator::TemplateValues;
+/// #
+/// let template = Template::parse("\
+/// typedef struct {
+///     {{#each members}}
+///     {{type}} {{name:member}};
+///     {{/each}}
+/// } {{name:type}};
+///
+/// void {{init:function}}({{name:type}} *self)
+/// {
+///     {{body}}
+/// }
+/// ").unwrap();
+///
+/// let member = |type_name: &str, name: &str| TemplateValues::new()
+///     .with_name("type", Name::new_with_type(type_name, NameType::Bypass))
+///     .with_name("name", name);
+/// let values = TemplateValues::new()
+///     .with_name("name", "uartConfig")
+///     .with_name("init", "uartConfigInit")
+///     .with_list("members", vec![member("uint32_t", "baudRate"), member("bool", "useParity")])
+///     .with_code("body", CodeSet::new(vec![
+///         Box::new("self->baud_rate = 9600;"),
+///         Box::new(IfStatement::new("PARITY_ENABLED", CodeBody::new(vec![Box::new("self->use_parity = true;")]))),
+///     ]));
+///
+/// let info = CodeGenerationInfo::from_style(CodeStyle::KnR).with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+/// "typedef struct {
+///     uint32_t baud_rate;
+///     bool use_parity;
+/// } UartConfig;
+///
+/// void uart_config_init(UartConfig *self)
+/// {
+///     self->baud_rate = 9600;
+///     if (PARITY_ENABLED) {
+///         self->use_parity = true;
+///     }
+/// }
+/// ", template.render(&values, &info).unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Parses a template, see Template for the placeholders
+    ///
+    /// ```
+    /// # use code_generator::Template;
+    /// #
+    /// let error = Template::parse("{{#each values}}\n{{value:kind}}\n{{/each}}").unwrap_err();
+    /// assert_eq!("line 2: unknown name type 'kind'", error.to_string());
+    ///
+    /// let error = Template::parse("{{#each values}}\n{{value}}\n").unwrap_err();
+    /// assert_eq!("line 1: '{{#each values}}' is never closed", error.to_string());
+    ///
+    /// assert!(Template::parse("int table[1][1] = {{0}};").is_ok());
+    /// ```
+    pub fn parse(text: &str) -> Result<Template, TemplateError> {
+        let text = text.replace("\r\n", "\n");
+        // Sections being parsed, with the line, key and parts of each
+        let mut sections: Vec<(usize, String, Vec<Part>)> = vec![(1, String::new(), Vec::new())];
+        let mut pending = String::new();
+        let mut rest = text.as_str();
+        let mut line = 1;
+        // Whether a tag was found on the current line before the pending text
+        let mut is_after_tag = false;
+
+        while let Some(start) = rest.find("{{") {
+            pending.push_str(&rest[..start]);
+            line += rest[..start].matches('\n').count();
+            let tag = match rest[start..].find("}}") {
+                Some(end) if Template::is_tag(rest[start + 2..start + end].trim()) => {
+                    let tag = rest[start + 2..start + end].trim();
+                    rest = &rest[start + end + 2..];
+                    tag
+                }
+                None if Template::is_tag(rest[start + 2..].trim_start()) => return Err(TemplateError::Syntax {
+                    line,
+                    message: String::from("'{{' is never closed"),
+                }),
+                _ => {
+                    // not a tag, keep the first brace and look for a tag after it
+                    pending.push('{');
+                    rest = &rest[start + 1..];
+                    continue;
+                }
+            };
+
+            if pending.contains('\n') {
+                is_after_tag = false;
+            }
+            let is_section = tag.starts_with('#') || tag.starts_with('/');
+            let tag_line = line;
+            if is_section && !is_after_tag {
+                rest = Template::trim_standalone(&mut pending, rest, &mut line);
+            }
+            is_after_tag = line == tag_line;
+            if !pending.is_empty() {
+                let parts = &mut sections.last_mut().unwrap().2;
+                parts.push(Part::Text(std::mem::take(&mut pending)));
+            }
+
+            if let Some(section) = tag.strip_prefix('#') {
+                match section.split_whitespace().collect::<Vec<_>>().as_slice() {
+                    ["each", key] => sections.push((tag_line, key.to_string(), Vec::new())),
+                    _ => return Err(TemplateError::Syntax {
+                        line: tag_line,
+                        message: format!("unknown section '{{{{{}}}}}', expected '{{{{#each key}}}}'", tag),
+                    }),
+                }
+            } else if tag == "/each" {
+                if sections.len() == 1 {
+                    return Err(TemplateError::Syntax { line: tag_line, message: String::from("'{{/each}}' without '{{#each}}'") });
+                }
+                let (each_line, key, body) = sections.pop().unwrap();
+                sections.last_mut().unwrap().2.push(Part::Each { line: each_line, key, body });
+            } else {
+                let (key, name_type) = match tag.split_once(':') {
+                    Some((key, name_type)) => {
+                        let name_type = NAME_TYPES.iter()
+                            .find(|(text, _)| *text == name_type.trim())
+                            .map(|(_, name_type)| name_type.clone())
+                            .ok_or_else(|| TemplateError::Syntax {
+                                line: tag_line,
+                                message: format!("unknown name type '{}'", name_type.trim()),
+                            })?;
+                        (key.trim(), Some(name_type))
+                    }
+                    None => (tag, None),
+                };
+                sections.last_mut().unwrap().2.push(Part::Value { line: tag_line, key: key.to_string(), name_type });
+            }
+        }
+        pending.push_str(rest);
+
+        let (section_line, key, mut parts) = sections.pop().unwrap();
+        if !sections.is_empty() {
+            return Err(TemplateError::Syntax {
+                line: section_line,
+                message: format!("'{{{{#each {}}}}}' is never closed", key),
+            });
+        }
+        if !pending.is_empty() {
+            parts.push(Part::Text(pending));
+        }
+        Ok(Template { parts })
+    }
+
+    /// Whether the text between braces is a tag, or the start of one when the
+    /// closing braces are missing
+    fn is_tag(text: &str) -> bool {
+        if text.starts_with('#') || text.starts_with('/') {
+            return true;
+        }
+        let key = text.split_once(':').map_or(text, |(key, _)| key).trim_end();
+        let mut chars = key.chars();
+        chars.next().is_some_and(|char| char.is_alphabetic() || char == '_')
+            && chars.all(|char| char.is_alphanumeric() || matches!(char, '_' | '-' | '.'))
+    }
+
+    /// Loads a template from a file, see Template for the placeholders
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Template, TemplateError> {
+        let text = std::fs::read_to_string(path)?;
+        Template::parse(&text)
+    }
+
+    /// Removes the line of a section tag which is alone on its line,
+    /// returning the text after it
+    ///
+    /// The pending text must start at the start of a line.
+    fn trim_standalone<'a>(pending: &mut String, rest: &'a str, line: &mut usize) -> &'a str {
+        let line_start = pending.rfind('\n').map_or(0, |index| index + 1);
+        let is_alone_before = pending[line_start..].chars().all(|c| c == ' ' || c == '\t');
+        let line_end = rest.find('\n');
+        let after = &rest[..line_end.unwrap_or(rest.len())];
+        let is_alone_after = after.chars().all(|c| c == ' ' || c == '\t');
+        if !is_alone_before || !is_alone_after {
+            return rest;
+        }
+
+        pending.truncate(line_start);
+        match line_end {
+            Some(line_end) => {
+                *line += 1;
+                &rest[line_end + 1..]
+            }
+            None => "",
+        }
+    }
+
+    /// Renders the template with the values
+    ///
+    /// The new lines of the template are replaced by the new line type of the
+    /// generation info. Names and code which fail to generate, like names
+    /// rejected by the identifier policy, give TemplateError::Generate.
+    pub fn render(&self, values: &TemplateValues, info: &CodeGenerationInfo) -> Result<String, TemplateError> {
+        let mut output = String::new();
+        Template::render_parts(&self.parts, &[values], &info.clone().with_new_line_type(NewLineType::Nl), &mut output)?;
+
+        let new_line = format!("{}", NewLine::new().display(info));
+        Ok(if new_line == "\n" { output } else { output.replace('\n', &new_line) })
+    }
+
+    fn render_parts(
+        parts: &[Part],
+        scopes: &[&TemplateValues],
+        info: &CodeGenerationInfo,
+        output: &mut String
+    ) -> Result<(), TemplateError> {
+        let lookup = |line: usize, key: &str| scopes.iter().rev()
+            .find_map(|scope| scope.get(key))
+            .ok_or_else(|| TemplateError::MissingValue { line, key: key.to_string() });
+        let generate = |line: usize, key: &str, code: &dyn CodeGenerate| try_render_generator(code, info)
+            .map_err(|error| TemplateError::Generate { line, key: key.to_string(), error });
+
+        for part in parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Value { line, key, name_type } => match (lookup(*line, key)?, name_type) {
+                    (TemplateValue::Name(name), Some(name_type)) => {
+                        output.push_str(&generate(*line, key, &name.clone().with_type(name_type.clone()))?);
+                    }
+                    (TemplateValue::Name(name), None) => output.push_str(&generate(*line, key, name)?),
+                    (TemplateValue::Code(code), None) => {
+                        let info = CodeGenerationInfo { indent_level: 0, ..info.clone() };
+                        let code = try_render_generator(code.as_ref(), &info)
+                            .map_err(|error| TemplateError::Generate { line: *line, key: key.clone(), error })?;
+                        Template::push_code(&code, output);
+                    }
+                    (TemplateValue::Code(_), Some(_)) => return Err(TemplateError::WrongValue {
+                        line: *line,
+                        key: key.clone(),
+                        expected: String::from("a Name"),
+                    }),
+                    (TemplateValue::List(_), _) => return Err(TemplateError::WrongValue {
+                        line: *line,
+                        key: key.clone(),
+                        expected: String::from("a Name or code, use '{{#each}}' for lists"),
+                    }),
+                },
+                Part::Each { line, key, body } => match lookup(*line, key)? {
+                    TemplateValue::List(items) => {
+                        for item in items {
+                            let mut item_scopes = scopes.to_vec();
+                            item_scopes.push(item);
+                            Template::render_parts(body, &item_scopes, info, output)?;
+                        }
+                    }
+                    _ => return Err(TemplateError::WrongValue {
+                        line: *line,
+                        key: key.clone(),
+                        expected: String::from("a list"),
+                    }),
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds generated code, indenting the lines after the first one to the
+    /// column the code starts at
+    fn push_code(code: &str, output: &mut String) {
+        let line_start = output.rfind('\n').map_or(0, |index| index + 1);
+        let before = &output[line_start..];
+        let indentation = if before.chars().all(|c| c == ' ' || c == '\t') {
+            before.to_string()
+        } else {
+            " ".repeat(before.chars().count())
+        };
+
+        for (index, code_line) in code.split('\n').enumerate() {
+            if index > 0 {
+                output.push('\n');
+                if !code_line.is_empty() {
+                    output.push_str(&indentation);
+                }
+            }
+            output.push_str(code_line);
+        }
+    }
+}
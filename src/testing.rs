@@ -0,0 +1,294 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use crate::setup::*;
+
+/// The environment variable which makes snapshots write their files
+///
+/// Any value other than an empty one or `0` blesses the output.
+pub const BLESS_VAR: &str = "CODE_GENERATOR_BLESS";
+
+/// Compares the output of a generator under several styles with golden files
+///
+/// Every style is rendered with `\n` new lines into its own file, named
+/// `<name>.<style>.c` in the snapshot directory. When the output differs, the
+/// check fails with a unified diff for every style that differs. When the
+/// bless environment variable is set, the files are written instead, so new
+/// output can be reviewed in the diff of the golden files.
+///
+/// ```
+/// # use code_generator::CodeStyle;
+/// # use code_generator::IfStatement;
+/// # use code_generator::CodeBody;
+/// # use code_generator::Snapshot;
+/// #
+/// let code = IfStatement::new("ready", CodeBody::new(vec![Box::new("run();")]));
+/// let directory = std::env::temp_dir().join("code_generator_snapshot_doc");
+/// let snapshot = Snapshot::new(&directory, "if_statement")
+///     .with_styles(&[CodeStyle::KnR, CodeStyle::Allman])
+///     .with_bless(false);
+///
+/// snapshot.clone().with_bless(true).assert(&code);
+/// assert_eq!(
+/// "if (ready) {
+///     run();
+/// }", std::fs::read_to_string(snapshot.path(CodeStyle::KnR)).unwrap());
+///
+/// snapshot.assert(&code);
+/// let changed = IfStatement::new("ready", CodeBody::new(vec![Box::new("stop();")]));
+/// assert!(snapshot.check(&changed).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Snapshot {
+    directory: PathBuf,
+    name: String,
+    styles: Vec<CodeStyle>,
+    bless: bool,
+}
+
+impl Snapshot {
+    /// Creates a snapshot checked under every style preset which can be
+    /// generated, see IndentationStyle::is_supported
+    ///
+    /// Whether the output is blessed comes from the bless environment
+    /// variable.
+    pub fn new(directory: impl Into<PathBuf>, name: impl Into<String>) -> Snapshot {
+        let bless = std::env::var(BLESS_VAR).is_ok_and(|value| !value.is_empty() && value != "0");
+        Snapshot {
+            directory: directory.into(),
+            name: name.into(),
+            styles: CodeStyle::ALL.into_iter()
+                .filter(|style| CodeGenerationInfo::from_style(*style).indent_style.is_supported())
+                .collect(),
+            bless,
+        }
+    }
+
+    /// Limits the snapshot to some styles
+    pub fn with_styles(mut self, styles: &[CodeStyle]) -> Self {
+        self.styles = styles.to_vec();
+        self
+    }
+
+    /// Writes the golden files instead of comparing with them
+    pub fn with_bless(mut self, bless: bool) -> Self {
+        self.bless = bless;
+        self
+    }
+
+    /// The golden file of a style
+    pub fn path(&self, style: CodeStyle) -> PathBuf {
+        self.directory.join(format!("{}.{:?}.c", self.name, style))
+    }
+
+    /// Compares the output of a generator with the golden files, or writes them
+    ///
+    /// Nothing is written when a style cannot be generated or the generator
+    /// fails, even when blessing.
+    pub fn check(&self, generator: &dyn CodeGenerate) -> Result<(), SnapshotError> {
+        let mut mismatches = Vec::new();
+        for &style in &self.styles {
+            let info = CodeGenerationInfo::from_style(style).with_new_line_type(NewLineType::Nl);
+            if !info.indent_style.is_supported() {
+                return Err(SnapshotError::UnsupportedStyle(style));
+            }
+            let actual = try_render_generator(generator, &info)
+                .map_err(|error| SnapshotError::Generate { style, error })?;
+            let path = self.path(style);
+
+            let expected = match fs::read_to_string(&path) {
+                Ok(expected) => Some(expected.replace("\r\n", "\n")),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                Err(error) => return Err(SnapshotError::Io { path, error }),
+            };
+            if expected.as_deref() == Some(actual.as_str()) {
+                continue;
+            }
+
+            if self.bless {
+                fs::create_dir_all(&self.directory)
+                    .and_then(|_| fs::write(&path, &actual))
+                    .map_err(|error| SnapshotError::Io { path: path.clone(), error })?;
+            } else {
+                let diff = expected.map(|expected| unified_diff(&expected, &actual, 3));
+                mismatches.push(SnapshotMismatch { style, path, diff });
+            }
+        }
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotError::Mismatch(mismatches))
+        }
+    }
+
+    /// Like check, but panics with the diffs when the output differs
+    #[track_caller]
+    pub fn assert(&self, generator: &dyn CodeGenerate) {
+        if let Err(error) = self.check(generator) {
+            panic!("snapshot '{}' failed\n{}", self.name, error);
+        }
+    }
+}
+
+/// A style whose output does not match its golden file
+#[derive(Clone, Debug)]
+pub struct SnapshotMismatch {
+    pub style: CodeStyle,
+    pub path: PathBuf,
+    /// The diff from the golden file to the output, None if there is no file
+    pub diff: Option<String>,
+}
+
+impl fmt::Display for SnapshotMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.diff {
+            Some(diff) => write!(f, "{:?} differs from {}:\n{}", self.style, self.path.display(), diff),
+            None => writeln!(f, "{:?} has no golden file at {}", self.style, self.path.display()),
+        }
+    }
+}
+
+/// The error returned when a snapshot does not match
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io { path: PathBuf, error: io::Error },
+    /// The style has no generated form to compare, see
+    /// IndentationStyle::is_supported
+    UnsupportedStyle(CodeStyle),
+    Generate { style: CodeStyle, error: GenerateError },
+    Mismatch(Vec<SnapshotMismatch>),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io { path, error } => write!(f, "could not access {}: {}", path.display(), error),
+            SnapshotError::UnsupportedStyle(style) => write!(f, "{:?} cannot be generated", style),
+            SnapshotError::Generate { style, error } => write!(f, "could not generate {:?}: {}", style, error),
+            SnapshotError::Mismatch(mismatches) => {
+                for mismatch in mismatches {
+                    write!(f, "{}", mismatch)?;
+                }
+                write!(f, "set {}=1 to write the new output to the golden files", BLESS_VAR)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Io { error, .. } => Some(error),
+            SnapshotError::Generate { error, .. } => Some(error),
+            SnapshotError::UnsupportedStyle(_) | SnapshotError::Mismatch(_) => None,
+        }
+    }
+}
+
+/// A line of a diff
+#[derive(Clone, Copy)]
+enum Edit<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Writes a unified diff from one text to another
+///
+/// Changes are grouped in hunks with `context` unchanged lines around them.
+/// Identical texts give an empty diff.
+///
+/// ```
+/// # use code_generator::unified_diff;
+/// #
+/// assert_eq!(
+/// "--- expected
+/// +++ actual
+/// @@ -1,3 +1,3 @@
+///  a
+/// -b
+/// +B
+///  c
+/// ", unified_diff("a\nb\nc", "a\nB\nc", 3));
+/// ```
+pub fn unified_diff(expected: &str, actual: &str, context: usize) -> String {
+    let edits = diff_lines(&expected.split('\n').collect::<Vec<_>>(), &actual.split('\n').collect::<Vec<_>>());
+    let changes: Vec<usize> = edits.iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Same(_)))
+        .map(|(index, _)| index)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &change in &changes {
+        let start = change.saturating_sub(context);
+        let end = (change + 1 + context).min(edits.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = String::from("--- expected\n+++ actual\n");
+    for (start, end) in hunks {
+        let old_before = edits[..start].iter().filter(|edit| !matches!(edit, Edit::Added(_))).count();
+        let new_before = edits[..start].iter().filter(|edit| !matches!(edit, Edit::Removed(_))).count();
+        let old_count = edits[start..end].iter().filter(|edit| !matches!(edit, Edit::Added(_))).count();
+        let new_count = edits[start..end].iter().filter(|edit| !matches!(edit, Edit::Removed(_))).count();
+        let range = |before: usize, count: usize| match count {
+            0 => format!("{},0", before),
+            count => format!("{},{}", before + 1, count),
+        };
+        diff.push_str(&format!("@@ -{} +{} @@\n", range(old_before, old_count), range(new_before, new_count)));
+
+        for edit in &edits[start..end] {
+            let (prefix, line) = match edit {
+                Edit::Same(line) => (' ', line),
+                Edit::Removed(line) => ('-', line),
+                Edit::Added(line) => ('+', line),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+/// Finds the edits from one list of lines to another through their longest
+/// common subsequence
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Edit<'a>> {
+    // lengths[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            edits.push(Edit::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            edits.push(Edit::Removed(old[i]));
+            i += 1;
+        } else {
+            edits.push(Edit::Added(new[j]));
+            j += 1;
+        }
+    }
+    edits
+}
@@ -0,0 +1,305 @@
+use crate::building_block_generators::*;
+use crate::data_type_generators::*;
+use crate::flow_control_generators::*;
+use crate::node::{Node, NodeMut};
+use crate::setup::*;
+
+/// Walks a tree of generators, calling the method matching the kind of each
+/// generator before visiting its children
+///
+/// All methods do nothing by default, so a visitor only implements the ones
+/// it is interested in.
+///
+/// ```
+/// # use code_generator::CodeSet;
+/// # use code_generator::Function;
+/// # use code_generator::FunctionDeclaration;
+/// # use code_generator::FunctionSignature;
+/// # use code_generator::Name;
+/// # use code_generator::Visitor;
+/// #
+/// struct DeclaredFunctions(Vec<String>);
+///
+/// impl Visitor for DeclaredFunctions {
+///     fn visit_function_signature(&mut self, signature: &FunctionSignature) {
+///         self.0.push(signature.name().source().to_string());
+///     }
+/// }
+///
+/// let code = CodeSet::new(vec![
+///     Box::new(FunctionDeclaration::new(Name::new("void"), Name::new("uartInit"), vec![])),
+///     Box::new(Function::new(
+///         FunctionSignature::new(Name::new("void"), Name::new("uartInit"), vec![]),
+///         CodeSet::new(vec![])
+///     )),
+/// ]);
+/// let mut functions = DeclaredFunctions(Vec::new());
+/// functions.visit(&code);
+/// assert_eq!(vec!["uartInit", "uartInit"], functions.0);
+/// ```
+pub trait Visitor {
+    fn visit_indentation(&mut self) {}
+    fn visit_new_line(&mut self) {}
+    fn visit_name(&mut self, _name: &Name) {}
+    fn visit_text(&mut self, _text: &str) {}
+    fn visit_text_block(&mut self, _text_block: &TextBlock) {}
+    fn visit_include(&mut self, _include: &Include) {}
+    fn visit_code_set(&mut self, _set: &CodeSet) {}
+    fn visit_joined_code(&mut self, _joined: &JoinedCode) {}
+    fn visit_separated_code(&mut self, _separated: &SeparatedCode) {}
+    fn visit_binary_operation(&mut self, _operation: &BinaryOperation) {}
+    fn visit_parameter_list(&mut self, _list: &ParameterList) {}
+    fn visit_code_body(&mut self, _body: &CodeBody) {}
+    fn visit_header_plus_body(&mut self, _header: &dyn CodeGenerate, _body: &CodeBody) {}
+    fn visit_control_header(&mut self, _header: &ControlHeader) {}
+    fn visit_if_statement(&mut self, _statement: &IfStatement) {}
+    fn visit_while_statement(&mut self, _statement: &WhileStatement) {}
+    fn visit_for_loop(&mut self, _statement: &ForLoop) {}
+    fn visit_parameter(&mut self, _parameter: &Parameter) {}
+    fn visit_function_signature(&mut self, _signature: &FunctionSignature) {}
+    fn visit_function_declaration(&mut self, _declaration: &FunctionDeclaration) {}
+    fn visit_function(&mut self, _function: &Function) {}
+    fn visit_function_call(&mut self, _call: &FunctionCall) {}
+    fn visit_header_file(&mut self, _header_file: &HeaderFile) {}
+    fn visit_enum(&mut self, _value: &Enum) {}
+    fn visit_struct(&mut self, _value: &Struct) {}
+    fn visit_type_def(&mut self, _type_def: &TypeDef) {}
+    fn visit_const_define(&mut self, _name: &Name, _value: &dyn CodeGenerate) {}
+    fn visit_custom(&mut self, _generator: &dyn CodeGenerate) {}
+
+    /// Visits a generator and all of its children
+    fn visit(&mut self, generator: &dyn CodeGenerate) {
+        walk(self, generator);
+    }
+}
+
+/// Calls the visitor method matching the generator, then visits its children
+pub fn walk<V: Visitor + ?Sized>(visitor: &mut V, generator: &dyn CodeGenerate) {
+    match generator.node() {
+        Node::Indentation => visitor.visit_indentation(),
+        Node::NewLine => visitor.visit_new_line(),
+        Node::Name(name) => visitor.visit_name(name),
+        Node::Text(text) => visitor.visit_text(text),
+        Node::TextBlock(text_block) => visitor.visit_text_block(text_block),
+        Node::Include(include) => visitor.visit_include(include),
+        Node::CodeSet(set) => visitor.visit_code_set(set),
+        Node::JoinedCode(joined) => visitor.visit_joined_code(joined),
+        Node::SeparatedCode(separated) => visitor.visit_separated_code(separated),
+        Node::BinaryOperation(operation) => visitor.visit_binary_operation(operation),
+        Node::ParameterList(list) => visitor.visit_parameter_list(list),
+        Node::CodeBody(body) => visitor.visit_code_body(body),
+        Node::HeaderPlusBody { header, body } => visitor.visit_header_plus_body(header, body),
+        Node::ControlHeader(header) => visitor.visit_control_header(header),
+        Node::IfStatement(statement) => visitor.visit_if_statement(statement),
+        Node::WhileStatement(statement) => visitor.visit_while_statement(statement),
+        Node::ForLoop(statement) => visitor.visit_for_loop(statement),
+        Node::Parameter(parameter) => visitor.visit_parameter(parameter),
+        Node::FunctionSignature(signature) => visitor.visit_function_signature(signature),
+        Node::FunctionDeclaration(declaration) => visitor.visit_function_declaration(declaration),
+        Node::Function(function) => visitor.visit_function(function),
+        Node::FunctionCall(call) => visitor.visit_function_call(call),
+        Node::HeaderFile(header_file) => visitor.visit_header_file(header_file),
+        Node::Enum(value) => visitor.visit_enum(value),
+        Node::Struct(value) => visitor.visit_struct(value),
+        Node::TypeDef(type_def) => visitor.visit_type_def(type_def),
+        Node::ConstDefine { name, value } => visitor.visit_const_define(name, value),
+        Node::Custom => visitor.visit_custom(generator),
+    }
+
+    for child in generator.children() {
+        visitor.visit(child);
+    }
+}
+
+/// Walks a tree of generators like Visitor, allowing the generators to be
+/// modified
+///
+/// The children of a generator are visited after it is modified, so a
+/// visitor can remove or reorder the children of a CodeSet or CodeBody
+/// before they are visited.
+///
+/// ```
+/// # use code_generator::CodeBody;
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeSet;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::Function;
+/// # use code_generator::FunctionSignature;
+/// # use code_generator::Name;
+/// # use code_generator::NewLineType;
+/// # use code_generator::Node;
+/// # use code_generator::VisitorMut;
+/// #
+/// struct StripDebugLogs;
+///
+/// impl VisitorMut for StripDebugLogs {
+///     fn visit_code_body_mut(&mut self, body: &mut CodeBody) {
+///         body.items_mut().retain(|item| !matches!(
+///             item.node(),
+///             Node::Text(text) if text.starts_with("DEBUG_LOG")
+///         ));
+///     }
+/// }
+///
+/// let mut code = Function::new(
+///     FunctionSignature::new(Name::new("void"), Name::new("start"), vec![]),
+///     CodeSet::new(vec![
+///         Box::new(String::from("DEBUG_LOG(\"start\");")),
+///         Box::new(String::from("run();")),
+///     ])
+/// );
+/// StripDebugLogs.visit_mut(&mut code);
+/// let info = CodeGenerationInfo::new().with_new_line_type(NewLineType::Nl);
+/// assert_eq!("Void start()\n{\n    run();\n}", format!("{}", code.display(&info)));
+/// ```
+pub trait VisitorMut {
+    fn visit_indentation_mut(&mut self) {}
+    fn visit_new_line_mut(&mut self) {}
+    fn visit_name_mut(&mut self, _name: &mut Name) {}
+    fn visit_text_mut(&mut self, _text: &mut String) {}
+    fn visit_text_block_mut(&mut self, _text_block: &mut TextBlock) {}
+    fn visit_include_mut(&mut self, _include: &mut Include) {}
+    fn visit_code_set_mut(&mut self, _set: &mut CodeSet) {}
+    fn visit_joined_code_mut(&mut self, _joined: &mut JoinedCode) {}
+    fn visit_separated_code_mut(&mut self, _separated: &mut SeparatedCode) {}
+    fn visit_binary_operation_mut(&mut self, _operation: &mut BinaryOperation) {}
+    fn visit_parameter_list_mut(&mut self, _list: &mut ParameterList) {}
+    fn visit_code_body_mut(&mut self, _body: &mut CodeBody) {}
+    fn visit_header_plus_body_mut(&mut self, _header: &mut dyn CodeGenerate, _body: &mut CodeBody) {}
+    fn visit_control_header_mut(&mut self, _header: &mut ControlHeader) {}
+    fn visit_if_statement_mut(&mut self, _statement: &mut IfStatement) {}
+    fn visit_while_statement_mut(&mut self, _statement: &mut WhileStatement) {}
+    fn visit_for_loop_mut(&mut self, _statement: &mut ForLoop) {}
+    fn visit_parameter_mut(&mut self, _parameter: &mut Parameter) {}
+    fn visit_function_signature_mut(&mut self, _signature: &mut FunctionSignature) {}
+    fn visit_function_declaration_mut(&mut self, _declaration: &mut FunctionDeclaration) {}
+    fn visit_function_mut(&mut self, _function: &mut Function) {}
+    fn visit_function_call_mut(&mut self, _call: &mut FunctionCall) {}
+    fn visit_header_file_mut(&mut self, _header_file: &mut HeaderFile) {}
+    fn visit_enum_mut(&mut self, _value: &mut Enum) {}
+    fn visit_struct_mut(&mut self, _value: &mut Struct) {}
+    fn visit_type_def_mut(&mut self, _type_def: &mut TypeDef) {}
+    fn visit_const_define_mut(&mut self, _name: &mut Name, _value: &mut dyn CodeGenerate) {}
+    fn visit_custom_mut(&mut self, _generator: &mut dyn CodeGenerate) {}
+
+    /// Visits a generator and all of its children
+    fn visit_mut(&mut self, generator: &mut dyn CodeGenerate) {
+        walk_mut(self, generator);
+    }
+}
+
+/// Calls the visitor method matching the generator, then visits its children
+pub fn walk_mut<V: VisitorMut + ?Sized>(visitor: &mut V, generator: &mut dyn CodeGenerate) {
+    match generator.node_mut() {
+        NodeMut::Indentation => visitor.visit_indentation_mut(),
+        NodeMut::NewLine => visitor.visit_new_line_mut(),
+        NodeMut::Name(name) => visitor.visit_name_mut(name),
+        NodeMut::Text(text) => visitor.visit_text_mut(text),
+        NodeMut::TextBlock(text_block) => visitor.visit_text_block_mut(text_block),
+        NodeMut::Include(include) => visitor.visit_include_mut(include),
+        NodeMut::CodeSet(set) => visitor.visit_code_set_mut(set),
+        NodeMut::JoinedCode(joined) => visitor.visit_joined_code_mut(joined),
+        NodeMut::SeparatedCode(separated) => visitor.visit_separated_code_mut(separated),
+        NodeMut::BinaryOperation(operation) => visitor.visit_binary_operation_mut(operation),
+        NodeMut::ParameterList(list) => visitor.visit_parameter_list_mut(list),
+        NodeMut::CodeBody(body) => visitor.visit_code_body_mut(body),
+        NodeMut::HeaderPlusBody { header, body } => visitor.visit_header_plus_body_mut(header, body),
+        NodeMut::ControlHeader(header) => visitor.visit_control_header_mut(header),
+        NodeMut::IfStatement(statement) => visitor.visit_if_statement_mut(statement),
+        NodeMut::WhileStatement(statement) => visitor.visit_while_statement_mut(statement),
+        NodeMut::ForLoop(statement) => visitor.visit_for_loop_mut(statement),
+        NodeMut::Parameter(parameter) => visitor.visit_parameter_mut(parameter),
+        NodeMut::FunctionSignature(signature) => visitor.visit_function_signature_mut(signature),
+        NodeMut::FunctionDeclaration(declaration) => visitor.visit_function_declaration_mut(declaration),
+        NodeMut::Function(function) => visitor.visit_function_mut(function),
+        NodeMut::FunctionCall(call) => visitor.visit_function_call_mut(call),
+        NodeMut::HeaderFile(header_file) => visitor.visit_header_file_mut(header_file),
+        NodeMut::Enum(value) => visitor.visit_enum_mut(value),
+        NodeMut::Struct(value) => visitor.visit_struct_mut(value),
+        NodeMut::TypeDef(type_def) => visitor.visit_type_def_mut(type_def),
+        NodeMut::ConstDefine { name, value } => visitor.visit_const_define_mut(name, value),
+        NodeMut::Custom => visitor.visit_custom_mut(generator),
+    }
+
+    for child in generator.children_mut() {
+        visitor.visit_mut(child);
+    }
+}
+
+/// A list of passes run over a tree of generators before it is displayed
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeSet;
+/// # use code_generator::DisplayExt;
+/// # use code_generator::FunctionDeclaration;
+/// # use code_generator::FunctionSignature;
+/// # use code_generator::Name;
+/// # use code_generator::NameType;
+/// # use code_generator::NewLineType;
+/// # use code_generator::Node;
+/// # use code_generator::PassPipeline;
+/// # use code_generator::VisitorMut;
+/// #
+/// struct PrefixFunctions(&'static str);
+///
+/// impl VisitorMut for PrefixFunctions {
+///     fn visit_function_signature_mut(&mut self, signature: &mut FunctionSignature) {
+///         *signature.name_mut() = signature.name().clone().prepend(self.0);
+///     }
+/// }
+///
+/// struct SortDeclarations;
+///
+/// impl VisitorMut for SortDeclarations {
+///     fn visit_code_set_mut(&mut self, set: &mut CodeSet) {
+///         set.items_mut().sort_by_key(|item| match item.node() {
+///             Node::FunctionDeclaration(declaration) => declaration.signature().name().clone(),
+///             _ => Name::new(""),
+///         });
+///     }
+/// }
+///
+/// let void = || Name::new_with_type("void", NameType::Bypass);
+/// let mut code = CodeSet::new(vec![
+///     Box::new(FunctionDeclaration::new(void(), Name::new("write"), vec![])),
+///     Box::new(FunctionDeclaration::new(void(), Name::new("init"), vec![])),
+/// ]);
+/// PassPipeline::new()
+///     .with_pass(SortDeclarations)
+///     .with_pass(PrefixFunctions("uart"))
+///     .run(&mut code);
+/// let info = CodeGenerationInfo::new().with_new_line_type(NewLineType::Nl);
+/// assert_eq!(
+///     "void uart_init();\nvoid uart_write();",
+///     format!("{}", code.display(&info))
+/// );
+/// ```
+pub struct PassPipeline {
+    passes: Vec<Box<dyn VisitorMut>>,
+}
+
+impl Default for PassPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PassPipeline {
+    pub fn new() -> PassPipeline {
+        PassPipeline { passes: Vec::new() }
+    }
+
+    /// Adds a pass, which runs after the passes added before it
+    pub fn with_pass(mut self, pass: impl VisitorMut + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Runs every pass over the whole tree, in order
+    pub fn run(&mut self, root: &mut dyn CodeGenerate) {
+        for pass in self.passes.iter_mut() {
+            pass.visit_mut(root);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fmt;
+use crate::building_block_generators::*;
+use crate::data_type_generators::*;
+use crate::flow_control_generators::*;
+use crate::setup::*;
+
+/// Where a CodeWriter puts the code it is given
+enum Output<'a> {
+    /// Writes every item as soon as it is given
+    Sink { sink: &'a mut dyn fmt::Write, is_empty: bool },
+    /// Collects the items of a body until it is complete
+    Body(Vec<Box<dyn CodeGenerate>>),
+}
+
+/// Writes code as it is described, instead of building a tree first
+///
+/// Lines and generators given to the writer are written to the sink right
+/// away, one per line. The body of a block is described in a closure and is
+/// written once the closure returns, through the same generators a built
+/// tree would use, so the brace style, indentation and context of the code
+/// follow the generation info.
+///
+/// ```
+/// # use code_generator::CodeGenerationInfo;
+/// # use code_generator::CodeStyle;
+/// # use code_generator::CodeWriter;
+/// # use code_generator::FunctionSignature;
+/// # use code_generator::Include;
+/// # use code_generator::Name;
+/// # use code_generator::NameType;
+/// # use code_generator::NewLineType;
+/// #
+/// let mut code = String::new();
+/// let info = CodeGenerationInfo::from_style(CodeStyle::Allman).with_new_line_type(NewLineType::Nl);
+/// let mut w = CodeWriter::new(&mut code, info);
+/// w.emit(&Include::new_sys("stdint.h"));
+/// w.blank_line();
+/// let signature = FunctionSignature::new(
+///     Name::new_with_type("void", NameType::Bypass),
+///     Name::new("uartPoll"),
+///     vec![]
+/// );
+/// w.function(signature, |w| {
+///     w.while_("uart_ready()", |w| {
+///         w.line("uint8_t byte = uart_read();");
+///         w.if_("byte == '\\n'", |w| {
+///             w.line("handle_line();");
+///         });
+///     });
+/// });
+/// w.finish().unwrap();
+///
+/// assert_eq!(
+/// "#include <stdint.h>
+///
+/// void uart_poll()
+/// {
+///     while (uart_ready())
+///     {
+///         uint8_t byte = uart_read();
+///         if (byte == '\\n')
+///         {
+///             handle_line();
+///         }
+///     }
+/// }", code);
+/// ```
+pub struct CodeWriter<'a> {
+    output: Output<'a>,
+    info: CodeGenerationInfo,
+    result: Result<(), GenerateError>,
+}
+
+impl<'a> CodeWriter<'a> {
+    /// Creates a writer which writes to a sink, like a String
+    pub fn new(sink: &'a mut dyn fmt::Write, info: CodeGenerationInfo) -> CodeWriter<'a> {
+        CodeWriter {
+            output: Output::Sink { sink, is_empty: true },
+            info,
+            result: Ok(()),
+        }
+    }
+
+    /// Writes any generator on its own line
+    pub fn emit(&mut self, generator: &(dyn CodeGenerate + 'static)) -> &mut Self {
+        self.push(generator.clone_boxed());
+        self
+    }
+
+    /// Writes a line of code
+    pub fn line(&mut self, text: impl Into<String>) -> &mut Self {
+        self.push(Box::new(text.into()));
+        self
+    }
+
+    /// Writes an empty line
+    pub fn blank_line(&mut self) -> &mut Self {
+        self.line("")
+    }
+
+    /// Writes a function with the body described by the closure
+    pub fn function(&mut self, signature: FunctionSignature, body: impl FnOnce(&mut CodeWriter)) -> &mut Self {
+        let body = self.body(body);
+        self.push(Box::new(Function::new(signature, CodeSet::new(body))));
+        self
+    }
+
+    /// Writes an if statement with the body described by the closure
+    pub fn if_(&mut self, condition: impl CodeGenerate + 'static, body: impl FnOnce(&mut CodeWriter)) -> &mut Self {
+        let body = self.body(body);
+        self.push(Box::new(IfStatement::new(condition, CodeBody::new(body))));
+        self
+    }
+
+    /// Writes a while loop with the body described by the closure
+    pub fn while_(&mut self, condition: impl CodeGenerate + 'static, body: impl FnOnce(&mut CodeWriter)) -> &mut Self {
+        let body = self.body(body);
+        self.push(Box::new(WhileStatement::new(condition, CodeBody::new(body))));
+        self
+    }
+
+    /// Writes a for loop with the body described by the closure
+    pub fn for_(
+        &mut self,
+        init: impl CodeGenerate + 'static,
+        condition: impl CodeGenerate + 'static,
+        update: impl CodeGenerate + 'static,
+        body: impl FnOnce(&mut CodeWriter)
+    ) -> &mut Self {
+        let body = self.body(body);
+        self.push(Box::new(ForLoop::new(init, condition, update, body)));
+        self
+    }
+
+    /// Writes any header followed by a body described by the closure
+    pub fn block(&mut self, header: impl CodeGenerate + Clone + 'static, body: impl FnOnce(&mut CodeWriter)) -> &mut Self {
+        let body = self.body(body);
+        self.push(Box::new(HeaderPlusBody::new(header, CodeBody::new(body))));
+        self
+    }
+
+    /// Returns the first error: why an item could not be generated, or
+    /// GenerateError::Failed if writing to the sink failed
+    ///
+    /// An item which could not be generated is not written.
+    pub fn finish(self) -> Result<(), GenerateError> {
+        self.result
+    }
+
+    /// Collects the items of a body described by a closure
+    fn body(&mut self, body: impl FnOnce(&mut CodeWriter)) -> Vec<Box<dyn CodeGenerate>> {
+        let mut writer = CodeWriter {
+            output: Output::Body(Vec::new()),
+            info: self.info.clone(),
+            result: Ok(()),
+        };
+        body(&mut writer);
+        if self.result.is_ok() {
+            self.result = writer.result;
+        }
+        match writer.output {
+            Output::Body(items) => items,
+            Output::Sink { .. } => Vec::new(),
+        }
+    }
+
+    /// Writes an item, or adds it to the body being collected
+    fn push(&mut self, item: Box<dyn CodeGenerate>) {
+        match &mut self.output {
+            Output::Sink { sink, is_empty } => {
+                let info = &self.info;
+                let result = try_render_generator(item.as_ref(), info).and_then(|code| {
+                    if !*is_empty {
+                        write!(sink, "{}{}", NewLine::new().display(info), Indentation::new().display(info))
+                            .map_err(|_| GenerateError::Failed)?;
+                    }
+                    *is_empty = false;
+                    sink.write_str(&code).map_err(|_| GenerateError::Failed)
+                });
+                if self.result.is_ok() {
+                    self.result = result;
+                }
+            }
+            Output::Body(items) => items.push(item),
+        }
+    }
+}